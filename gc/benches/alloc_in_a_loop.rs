@@ -18,6 +18,12 @@ fn keep(b: &mut test::Bencher, n: usize) {
         (0..n).map(|_| gc::Gc::new(THING)).collect::<Vec<_>>()
     });
 }
+fn keep_bulk(b: &mut test::Bencher, n: usize) {
+    b.iter(|| {
+        gc::force_collect();
+        gc::with_bulk_alloc(|| (0..n).map(|_| gc::Gc::new(THING)).collect::<Vec<_>>())
+    });
+}
 
 #[bench]
 fn discard_100(b: &mut test::Bencher) {
@@ -35,3 +41,7 @@ fn discard_10000(b: &mut test::Bencher) {
 fn keep_10000(b: &mut test::Bencher) {
     keep(b, 10_000);
 }
+#[bench]
+fn keep_bulk_10000(b: &mut test::Bencher) {
+    keep_bulk(b, 10_000);
+}
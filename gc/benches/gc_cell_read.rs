@@ -0,0 +1,29 @@
+#![feature(test)]
+
+extern crate test;
+
+use gc::{Gc, GcCell};
+
+const N: u32 = 10_000;
+
+fn cell() -> Gc<GcCell<u32>> {
+    Gc::new(GcCell::new(1))
+}
+
+#[bench]
+fn borrow_10000(b: &mut test::Bencher) {
+    let cell = cell();
+    b.iter(|| {
+        let cell = test::black_box(&cell);
+        (0..N).fold(0u32, |sum, _| sum.wrapping_add(*cell.borrow()))
+    });
+}
+
+#[bench]
+fn get_10000(b: &mut test::Bencher) {
+    let cell = cell();
+    b.iter(|| {
+        let cell = test::black_box(&cell);
+        (0..N).fold(0u32, |sum, _| sum.wrapping_add(cell.get()))
+    });
+}
@@ -0,0 +1,49 @@
+#![feature(test)]
+
+extern crate test;
+
+use gc::{Gc, GcCell};
+
+const OLD: usize = 100_000;
+const YOUNG: usize = 1_000;
+
+type Slot = Gc<GcCell<Gc<usize>>>;
+
+/// Keeps `OLD` long-lived boxes alive and promotes them, the way an
+/// interpreter's globals would be, along with an old slot to store a young
+/// box in.
+fn old_heap() -> (Gc<Vec<Gc<usize>>>, Slot) {
+    let old = Gc::new((0..OLD).map(Gc::new).collect());
+    let slot = Gc::new(GcCell::new(Gc::new(0)));
+    gc::minor_collect();
+    gc::minor_collect();
+    (old, slot)
+}
+
+/// Allocates `YOUNG` short-lived boxes, keeping one of them alive in the old
+/// slot, and then collects them with `collect`.
+fn churn(slot: &Slot, collect: fn()) {
+    for i in 0..YOUNG {
+        let young = test::black_box(Gc::new(i));
+        if i == 0 {
+            *slot.borrow_mut() = young;
+        }
+    }
+    collect();
+}
+
+#[bench]
+fn minor_collect_1000(b: &mut test::Bencher) {
+    let (_old, slot) = old_heap();
+    b.iter(|| {
+        churn(&slot, || {
+            gc::minor_collect();
+        })
+    });
+}
+
+#[bench]
+fn force_collect_1000(b: &mut test::Bencher) {
+    let (_old, slot) = old_heap();
+    b.iter(|| churn(&slot, gc::force_collect));
+}
@@ -0,0 +1,22 @@
+#![feature(test)]
+
+extern crate test;
+
+const N: usize = 1_000_000;
+
+/// Allocates `N` `Gc<u8>`s, keeping them alive, and returns the number of
+/// bytes they take on the garbage-collected heap: 32000000 by default, or
+/// 24000000 with the `compact-header` feature.
+fn keep_small() -> usize {
+    gc::force_collect();
+    let before = gc::stats().bytes_allocated;
+    let all = gc::with_bulk_alloc(|| (0..N).map(|i| gc::Gc::new(i as u8)).collect::<Vec<_>>());
+    let bytes = gc::stats().bytes_allocated - before;
+    drop(test::black_box(all));
+    bytes
+}
+
+#[bench]
+fn keep_small_1000000(b: &mut test::Bencher) {
+    b.iter(keep_small);
+}
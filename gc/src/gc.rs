@@ -1,9 +1,12 @@
-use crate::set_data_ptr;
-use crate::trace::Trace;
-use std::alloc::{alloc, dealloc, Layout};
+use crate::trace::{Finalize, Trace};
+use crate::{set_data_ptr, HeaderSlice};
+use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
 use std::cell::{Cell, RefCell};
-use std::mem;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
 use std::ptr::{self, NonNull};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "nightly")]
 use std::marker::Unsize;
@@ -11,13 +14,57 @@ use std::marker::Unsize;
 struct GcState {
     stats: GcStats,
     config: GcConfig,
-    boxes_start: Option<NonNull<GcBox<dyn Trace>>>,
+    allocator: Allocator,
+    boxes_start: Link,
+    // The number of active `with_bulk_alloc` calls. Allocations don't
+    // trigger collections while this is non-zero.
+    bulk_alloc_depth: usize,
+    // The number of boxes allocated since the last collection.
+    allocations: usize,
+    // Allocations don't trigger collections until this many bytes are
+    // allocated, as set by `reserve`. Every collection resets it.
+    reserved_until: usize,
+    // Whether this is the state of a `GcRegion`, rather than of the thread.
+    region: bool,
+    // The functions registered with `register_root_source`.
+    root_sources: Vec<RootSource>,
+    // The number of consecutive collections each rooted box was rooted at.
+    #[cfg(all(feature = "unstable-stats", debug_assertions))]
+    rooted_for: HashMap<GcPointer, usize>,
+}
+
+type RootSource = Box<dyn Fn(&mut dyn FnMut(GcPointer))>;
+
+impl GcState {
+    fn new(region: bool) -> Self {
+        GcState {
+            stats: GcStats::default(),
+            config: GcConfig::default(),
+            allocator: Allocator(None),
+            boxes_start: Link::new(None),
+            bulk_alloc_depth: 0,
+            allocations: 0,
+            reserved_until: 0,
+            root_sources: Vec::new(),
+            region,
+            #[cfg(all(feature = "unstable-stats", debug_assertions))]
+            rooted_for: HashMap::new(),
+        }
+    }
 }
 
 impl Drop for GcState {
     fn drop(&mut self) {
-        if !self.config.leak_on_drop {
-            collect_garbage(self);
+        if !self.region {
+            // The thread is exiting, and the listener may use thread-locals
+            // which are already gone.
+            let _ = PHASE_LISTENER.try_with(|l| l.borrow_mut().take());
+        }
+        if !self.config.leak_on_drop && self.root_sources.is_empty() {
+            collect_garbage(self, false);
+            if self.region {
+                deliver_phases();
+            }
         }
         // We have no choice but to leak any remaining nodes that
         // might be referenced from other thread-local variables.
@@ -44,20 +91,129 @@ pub fn finalizer_safe() -> bool {
     GC_DROPPING.with(|dropping| !dropping.get())
 }
 
+/// Runs `f` as if the collector weren't freeing objects, so that `f` can
+/// dereference `Gc`s even when called from a `Drop` impl during the sweep.
+///
+/// Finalizers don't need this, as they run before anything is freed. This
+/// is for `Drop` impls which need to reach objects they know are still
+/// alive, e.g. a registry which a dying object removes itself from.
+///
+/// # Safety
+///
+/// While the collector is freeing objects, any `Gc` which was unreachable
+/// in this collection may already be dangling. Within `f`, only `Gc`s to
+/// allocations which were reachable in this collection may be dereferenced,
+/// cloned or dropped: those reachable from a root, or which a finalizer made
+/// reachable again. In particular, the `Gc`s held by the object being
+/// dropped must not be touched unless they point to such allocations.
+/// `f` still can't allocate `Gc`s or collect, which panics as in a
+/// finalizer.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{force_collect, with_finalizer_access, Finalize, Gc, GcCell, Trace};
+///
+/// #[derive(Trace, Finalize)]
+/// #[unsafe_no_drop_check]
+/// struct Member {
+///     // Rooted by the caller for as long as any member lives.
+///     registry: Gc<GcCell<u32>>,
+/// }
+///
+/// impl Drop for Member {
+///     fn drop(&mut self) {
+///         // The registry is still reachable, so it isn't being freed.
+///         unsafe { with_finalizer_access(|| *self.registry.borrow_mut() -= 1) };
+///     }
+/// }
+///
+/// let registry = Gc::new(GcCell::new(1));
+/// drop(Gc::new(Member { registry: registry.clone() }));
+/// force_collect();
+/// assert_eq!(*registry.borrow(), 0);
+/// ```
+pub unsafe fn with_finalizer_access<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            GC_DROPPING.with(|dropping| dropping.set(self.0));
+        }
+    }
+
+    let _restore = Restore(GC_DROPPING.with(|dropping| dropping.replace(false)));
+    f()
+}
+
 // The garbage collector's internal state.
-thread_local!(static GC_STATE: RefCell<GcState> = RefCell::new(GcState {
-    stats: GcStats::default(),
-    config: GcConfig::default(),
-    boxes_start: None,
-}));
+thread_local!(static GC_STATE: RefCell<GcState> = RefCell::new(GcState::new(false)));
+
+// The root count and flags of a `GcBox`. With the `compact-header` feature,
+// they are packed into a `u32` rather than a `usize`, which shrinks the header
+// on 64-bit targets but lowers the root count limit.
+#[cfg(feature = "compact-header")]
+type RootsWord = u32;
+#[cfg(not(feature = "compact-header"))]
+type RootsWord = usize;
 
-const MARK_MASK: usize = 1 << (usize::BITS - 1);
-const ROOTS_MASK: usize = !MARK_MASK;
-const ROOTS_MAX: usize = ROOTS_MASK; // max allowed value of roots
+const MARK_MASK: RootsWord = 1 << (RootsWord::BITS - 1);
+const BORROWED_MASK: RootsWord = 1 << (RootsWord::BITS - 2);
+const FINALIZED_MASK: RootsWord = 1 << (RootsWord::BITS - 3);
+const UNINIT_MASK: RootsWord = 1 << (RootsWord::BITS - 4);
+const OLD_MASK: RootsWord = 1 << (RootsWord::BITS - 5);
+const DST_MASK: RootsWord = 1 << (RootsWord::BITS - 6);
+const ROOTS_MASK: RootsWord =
+    !(MARK_MASK | BORROWED_MASK | FINALIZED_MASK | UNINIT_MASK | OLD_MASK | DST_MASK);
+const ROOTS_MAX: RootsWord = ROOTS_MASK; // max allowed value of roots
 
+// The limit is documented on `Gc::clone`, update it there when taking a bit.
+const _: () = assert!(ROOTS_MAX.count_ones() == RootsWord::BITS - 6);
+
+// The next pointer is stored unaligned, so with `compact-header` the header is
+// kept to 4-byte alignment. On 64-bit targets it then takes 20 bytes, and
+// values with an alignment of up to 4 are stored right after it, rather than
+// after 4 bytes of padding.
 pub(crate) struct GcBoxHeader {
-    roots: Cell<usize>, // high bit is used as mark flag
-    next: Cell<Option<NonNull<GcBox<dyn Trace>>>>,
+    roots: Cell<RootsWord>, // high bits are used as mark, borrowed, finalized, uninit, old and dst flags
+    next: Link,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct Unaligned(Option<NonNull<GcBox<dyn Trace>>>);
+
+/// A link in the `GcBox` chain: a `Cell<Option<NonNull<GcBox<dyn Trace>>>>`
+/// with an alignment of 1.
+pub(crate) struct Link(Cell<Unaligned>);
+
+impl Link {
+    #[inline]
+    fn new(next: Option<NonNull<GcBox<dyn Trace>>>) -> Self {
+        Link(Cell::new(Unaligned(next)))
+    }
+
+    #[inline]
+    fn get(&self) -> Option<NonNull<GcBox<dyn Trace>>> {
+        self.0.get().0
+    }
+
+    #[inline]
+    fn set(&self, next: Option<NonNull<GcBox<dyn Trace>>>) {
+        self.0.set(Unaligned(next));
+    }
+
+    #[inline]
+    fn replace(
+        &self,
+        next: Option<NonNull<GcBox<dyn Trace>>>,
+    ) -> Option<NonNull<GcBox<dyn Trace>>> {
+        self.0.replace(Unaligned(next)).0
+    }
+
+    #[inline]
+    fn take(&self) -> Option<NonNull<GcBox<dyn Trace>>> {
+        self.replace(None)
+    }
 }
 
 impl GcBoxHeader {
@@ -65,13 +221,15 @@ impl GcBoxHeader {
     pub fn new() -> Self {
         GcBoxHeader {
             roots: Cell::new(1), // unmarked and roots count = 1
-            next: Cell::new(None),
+            next: Link::new(None),
         }
     }
 
     #[inline]
     pub fn roots(&self) -> usize {
-        self.roots.get() & ROOTS_MASK
+        #[allow(clippy::unnecessary_cast)]
+        let roots = (self.roots.get() & ROOTS_MASK) as usize;
+        roots
     }
 
     #[inline]
@@ -106,6 +264,73 @@ impl GcBoxHeader {
     pub fn unmark(&self) {
         self.roots.set(self.roots.get() & !MARK_MASK);
     }
+
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.roots.get() & BORROWED_MASK != 0
+    }
+
+    #[inline]
+    pub fn is_finalized(&self) -> bool {
+        self.roots.get() & FINALIZED_MASK != 0
+    }
+
+    #[inline]
+    pub fn set_finalized(&self, finalized: bool) {
+        if finalized {
+            self.roots.set(self.roots.get() | FINALIZED_MASK);
+        } else {
+            self.roots.set(self.roots.get() & !FINALIZED_MASK);
+        }
+    }
+
+    #[inline]
+    pub fn is_uninit(&self) -> bool {
+        self.roots.get() & UNINIT_MASK != 0
+    }
+
+    #[inline]
+    pub fn set_uninit(&self, uninit: bool) {
+        if uninit {
+            self.roots.set(self.roots.get() | UNINIT_MASK);
+        } else {
+            self.roots.set(self.roots.get() & !UNINIT_MASK);
+        }
+    }
+
+    /// Returns whether the box survived a collection. Boxes are promoted
+    /// from the nursery once they do, so old boxes are only freed by full
+    /// collections.
+    #[inline]
+    pub fn is_old(&self) -> bool {
+        self.roots.get() & OLD_MASK != 0
+    }
+
+    #[inline]
+    pub fn set_old(&self) {
+        self.roots.set(self.roots.get() | OLD_MASK);
+    }
+
+    /// Returns whether the box holds a `HeaderSlice`, whose chain entry
+    /// doesn't know the length of its slice.
+    #[inline]
+    pub fn is_dst(&self) -> bool {
+        self.roots.get() & DST_MASK != 0
+    }
+
+    #[inline]
+    pub fn set_dst(&self) {
+        self.roots.set(self.roots.get() | DST_MASK);
+    }
+
+    #[inline]
+    pub fn set_borrowed(&self, borrowed: bool) {
+        if borrowed {
+            self.roots.set(self.roots.get() | BORROWED_MASK);
+        } else {
+            self.roots.set(self.roots.get() & !BORROWED_MASK);
+        }
+    }
 }
 
 #[repr(C)] // to justify the layout computations in GcBox::from_box, Gc::from_raw
@@ -121,12 +346,88 @@ impl<T: Trace> GcBox<T> {
     ///
     /// A `GcBox` allocated this way starts its life rooted.
     pub(crate) fn new(value: T) -> NonNull<Self> {
-        let gcbox = NonNull::from(Box::leak(Box::new(GcBox {
-            header: GcBoxHeader::new(),
-            data: value,
-        })));
-        unsafe { insert_gcbox(gcbox) };
-        gcbox
+        GC_STATE.with(|st| Self::new_in(value, st))
+    }
+
+    /// Like `new`, but returns an error if the allocation fails, even after
+    /// collecting garbage to make room.
+    pub(crate) fn try_new(value: T) -> Result<NonNull<Self>, AllocError> {
+        GC_STATE.with(|st| Self::try_new_in(value, st))
+    }
+
+    /// Like `new`, but allocates in the `GcBox` chain of `state`.
+    fn new_in(value: T, state: &RefCell<GcState>) -> NonNull<Self> {
+        match Self::try_new_in(value, state) {
+            Ok(gcbox) => gcbox,
+            Err(AllocError) => handle_alloc_error(Layout::new::<Self>()),
+        }
+    }
+
+    fn try_new_in(value: T, state: &RefCell<GcState>) -> Result<NonNull<Self>, AllocError> {
+        let gcbox = Self::alloc_in(state)?;
+        unsafe {
+            gcbox.as_ptr().write(GcBox {
+                header: GcBoxHeader::new(),
+                data: value,
+            });
+            insert_gcbox(state, gcbox);
+        }
+        Ok(gcbox)
+    }
+
+    /// Allocates memory for a `GcBox` in the chain of `state`, collecting
+    /// garbage and retrying once if that fails.
+    fn alloc_in(state: &RefCell<GcState>) -> Result<NonNull<Self>, AllocError> {
+        let layout = Layout::new::<Self>();
+        let mut ptr = state.borrow().allocator.alloc(layout);
+        if ptr.is_null() {
+            collect_garbage(&mut state.borrow_mut(), false);
+            deliver_phases();
+            ptr = state.borrow().allocator.alloc(layout);
+        }
+        NonNull::new(ptr.cast::<Self>()).ok_or(AllocError)
+    }
+
+    /// Like `new`, but leaves the value uninitialized, or filled with zero
+    /// bytes if `zeroed` is set.
+    ///
+    /// The collector doesn't trace, finalize or drop the value until
+    /// `assume_init` is called.
+    pub(crate) fn new_uninit(zeroed: bool) -> NonNull<GcBox<MaybeUninit<T>>> {
+        GC_STATE.with(|st| {
+            let gcbox = match Self::alloc_in(st) {
+                Ok(gcbox) => gcbox,
+                Err(AllocError) => handle_alloc_error(Layout::new::<Self>()),
+            };
+            unsafe {
+                let header = GcBoxHeader::new();
+                header.set_uninit(true);
+                ptr::addr_of_mut!((*gcbox.as_ptr()).header).write(header);
+                if zeroed {
+                    ptr::addr_of_mut!((*gcbox.as_ptr()).data).write_bytes(0, 1);
+                }
+                // The chain entry keeps the vtable of `T`, so the value is
+                // traced as a `T` once it is initialized.
+                insert_gcbox(st, gcbox);
+            }
+            gcbox.cast()
+        })
+    }
+}
+
+impl<T: Trace> GcBox<MaybeUninit<T>> {
+    /// Lets the collector trace, finalize and drop the value of a `GcBox`
+    /// allocated with `new_uninit`, unrooting its `Gc`s now that it's on the
+    /// heap.
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized.
+    pub(crate) unsafe fn assume_init(&self) {
+        let value = self.data.assume_init_ref();
+        write_barrier(value);
+        value.unroot();
+        self.header.set_uninit(false);
     }
 }
 
@@ -147,10 +448,10 @@ impl<
         let gcbox_layout = header_layout.extend(value_layout).unwrap().0.pad_to_align();
 
         unsafe {
-            // Allocate the GcBox in a way that's compatible with Box,
-            // since the collector will deallocate it via
-            // Box::from_raw.
-            let gcbox_addr = alloc(gcbox_layout);
+            let gcbox_addr = GC_STATE.with(|st| st.borrow().allocator.alloc(gcbox_layout));
+            if gcbox_addr.is_null() {
+                handle_alloc_error(gcbox_layout);
+            }
 
             // Since we're not allowed to move the value out of an
             // active Box, and we will need to deallocate the Box
@@ -176,47 +477,725 @@ impl<
 
             // Add the new GcBox to the chain and return it.
             let gcbox = NonNull::new_unchecked(gcbox);
-            insert_gcbox(gcbox);
+            GC_STATE.with(|st| insert_gcbox(st, gcbox));
             gcbox
         }
     }
 }
 
-/// Add a new `GcBox` to the current thread's `GcBox` chain. This
+/// The size of a `GcBox<HeaderSlice<H, E>>`, and the length of its slice.
+/// This is the first field of a `HeaderSlice`, so the collector can find the
+/// size of any `GcBox` with the dst flag set.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct DstMeta {
+    size: usize,
+    len: usize,
+}
+
+/// The sized prefix of a `HeaderSlice<H, E>`, which its `GcBox` is stored as
+/// in the chain, as only sized values can be made into a `dyn Trace`. It
+/// delegates everything to the whole `HeaderSlice`.
+#[repr(C)]
+struct DstPrefix<H, E> {
+    meta: DstMeta,
+    header: ManuallyDrop<H>,
+    slice: [E; 0],
+}
+
+impl<H, E> DstPrefix<H, E> {
+    fn whole(&self) -> *mut HeaderSlice<H, E> {
+        let data = (self as *const Self).cast_mut().cast::<E>();
+        ptr::slice_from_raw_parts_mut(data, self.meta.len) as *mut HeaderSlice<H, E>
+    }
+}
+
+impl<H, E> Drop for DstPrefix<H, E> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.whole()) }
+    }
+}
+
+impl<H, E> Finalize for DstPrefix<H, E> {}
+
+unsafe impl<H: Trace, E: Trace> Trace for DstPrefix<H, E> {
+    #[inline]
+    unsafe fn trace(&self) {
+        (*self.whole()).trace();
+    }
+
+    #[inline]
+    unsafe fn root(&self) {
+        (*self.whole()).root();
+    }
+
+    #[inline]
+    unsafe fn unroot(&self) {
+        (*self.whole()).unroot();
+    }
+
+    #[inline]
+    fn finalize_glue(&self) {
+        unsafe { (*self.whole()).finalize_glue() }
+    }
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        unsafe { (*self.whole()).needs_finalize() }
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        (*self.whole()).visit_children(visitor);
+    }
+}
+
+impl<H: Trace + 'static, E: Trace + Clone + 'static> GcBox<HeaderSlice<H, E>> {
+    /// Allocates a `GcBox` holding `header` followed by clones of the
+    /// elements of `slice`, and appends it to the thread-local `GcBox`
+    /// chain. This might trigger a collection.
+    ///
+    /// A `GcBox` allocated this way starts its life rooted.
+    pub(crate) fn new_dst(header: H, slice: &[E]) -> NonNull<Self> {
+        let len = slice.len();
+        // This relies on GcBox and HeaderSlice being #[repr(C)].
+        let data_layout = Layout::new::<DstMeta>()
+            .extend(Layout::new::<H>())
+            .and_then(|(prefix, _)| prefix.extend(Layout::array::<E>(len)?))
+            .expect("capacity overflow")
+            .0;
+        let gcbox_layout = Layout::new::<GcBoxHeader>()
+            .extend(data_layout)
+            .expect("capacity overflow")
+            .0
+            .pad_to_align();
+
+        GC_STATE.with(|st| unsafe {
+            let mut gcbox_addr = st.borrow().allocator.alloc(gcbox_layout);
+            if gcbox_addr.is_null() {
+                collect_garbage(&mut st.borrow_mut(), false);
+                deliver_phases();
+                gcbox_addr = st.borrow().allocator.alloc(gcbox_layout);
+            }
+            if gcbox_addr.is_null() {
+                handle_alloc_error(gcbox_layout);
+            }
+
+            let gcbox = ptr::slice_from_raw_parts_mut(gcbox_addr.cast::<E>(), len) as *mut Self;
+            let box_header = GcBoxHeader::new();
+            box_header.set_dst();
+            ptr::addr_of_mut!((*gcbox).header).write(box_header);
+            ptr::addr_of_mut!((*gcbox).data.meta).write(DstMeta {
+                size: gcbox_layout.size(),
+                len,
+            });
+            ptr::addr_of_mut!((*gcbox).data.header).write(header);
+            // If a clone panics, the allocation is leaked, but it isn't in
+            // the chain yet, so the collector never sees it.
+            let elems = ptr::addr_of_mut!((*gcbox).data.slice).cast::<E>();
+            for (i, elem) in slice.iter().enumerate() {
+                elems.add(i).write(elem.clone());
+            }
+
+            let prefix = NonNull::new_unchecked(gcbox_addr.cast::<GcBox<DstPrefix<H, E>>>());
+            insert_gcbox(st, prefix);
+            NonNull::new_unchecked(gcbox)
+        })
+    }
+}
+
+impl GcBox<dyn Trace> {
+    /// Returns the layout this `GcBox` was allocated with.
+    fn layout(&self) -> Layout {
+        if self.header.is_dst() {
+            // The vtable is that of a `DstPrefix`, which knows the size.
+            let meta = unsafe { *(&self.data as *const dyn Trace).cast::<DstMeta>() };
+            unsafe { Layout::from_size_align_unchecked(meta.size, mem::align_of_val(self)) }
+        } else {
+            Layout::for_value(self)
+        }
+    }
+}
+
+/// An allocator for the memory of `Gc` allocations, installed with
+/// [`set_allocator`].
+///
+/// # Safety
+///
+/// As with `GlobalAlloc`, `alloc` must return either null or a block of
+/// memory fitting `layout`, which stays valid until passed to `dealloc`.
+pub unsafe trait GcAllocator {
+    /// Allocates memory as described by `layout`, or returns null on
+    /// failure. `layout` never has a size of zero.
+    fn alloc(&self, layout: Layout) -> *mut u8;
+
+    /// Deallocates the block of memory at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `alloc` with the same `layout`.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout);
+}
+
+// The allocator set with `set_allocator`, or the global allocator.
+struct Allocator(Option<Box<dyn GcAllocator>>);
+
+impl Allocator {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        match &self.0 {
+            Some(allocator) => allocator.alloc(layout),
+            None => unsafe { alloc(layout) },
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match &self.0 {
+            Some(allocator) => allocator.dealloc(ptr, layout),
+            None => dealloc(ptr, layout),
+        }
+    }
+}
+
+/// Makes the current thread allocate the memory of its `Gc`s with
+/// `allocator`, rather than the global allocator.
+///
+/// # Panics
+///
+/// Panics if the current thread has any `Gc` allocations, as they would be
+/// freed with the wrong allocator.
+pub fn set_allocator(allocator: impl GcAllocator + 'static) {
+    GC_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        assert!(
+            st.boxes_start.get().is_none(),
+            "set_allocator called after allocating a Gc"
+        );
+        st.allocator = Allocator(Some(Box::new(allocator)));
+    });
+}
+
+/// Add a new `GcBox` to the `GcBox` chain of `state`. This
 /// might trigger a collection first if enough bytes have been
-/// allocated since the previous collection.
+/// allocated since the previous collection, unless automatic
+/// collection is turned off.
 ///
 /// # Safety
 ///
 /// `gcbox` must point to a valid `GcBox` that is not yet in a `GcBox`
 /// chain.
-unsafe fn insert_gcbox(gcbox: NonNull<GcBox<dyn Trace>>) {
+unsafe fn insert_gcbox(state: &RefCell<GcState>, gcbox: NonNull<GcBox<dyn Trace>>) {
+    let mut st = state.borrow_mut();
+
+    if st.bulk_alloc_depth == 0 && st.config.auto_collect {
+        collect_if_needed(&mut st);
+    }
+
+    let next = st.boxes_start.replace(Some(gcbox));
+    gcbox.as_ref().header.next.set(next);
+
+    if !st.region {
+        let _ = INCREMENTAL.try_with(|inc| {
+            if let Some(inc) = &mut *inc.borrow_mut() {
+                inc.boxes.insert(GcPointer::new(gcbox), gcbox);
+            }
+        });
+    }
+
+    // We allocated some bytes! Let's record it
+    st.stats.bytes_allocated += gcbox.as_ref().layout().size();
+    st.allocations += 1;
+    #[cfg(feature = "unstable-stats")]
+    {
+        st.stats.peak_bytes_allocated = st.stats.peak_bytes_allocated.max(st.stats.bytes_allocated);
+    }
+    drop(st);
+    deliver_phases();
+}
+
+/// Collects garbage if enough bytes have been allocated since the previous
+/// collection.
+fn collect_if_needed(st: &mut GcState) {
+    if st.stats.bytes_allocated < st.reserved_until {
+        return;
+    }
+    // XXX We should probably be more clever about collecting
+    let bytes_due = st.stats.bytes_allocated > st.config.threshold;
+    let due = match st.config.trigger {
+        CollectionTrigger::Bytes => bytes_due,
+        CollectionTrigger::Allocations(n) => st.allocations >= n,
+        CollectionTrigger::Either(n) => bytes_due || st.allocations >= n,
+    };
+    if due {
+        collect_garbage(st, false);
+
+        if st.stats.bytes_allocated as f64 > st.config.threshold as f64 * st.config.used_space_ratio
+        {
+            // we didn't collect enough, so increase the
+            // threshold for next time, to avoid thrashing the
+            // collector too much/behaving quadratically.
+            st.config.threshold =
+                (st.stats.bytes_allocated as f64 / st.config.used_space_ratio) as usize;
+        }
+    }
+}
+
+/// Runs `f` without allowing the allocations it makes to trigger a garbage
+/// collection.
+///
+/// Every `Gc::new` normally checks whether enough has been allocated to
+/// warrant a collection, which can make the collector run many times while
+/// loading a large number of objects that all stay alive. Within `f`, the
+/// check is skipped, and it is instead performed once when `f` returns.
+/// Calls may be nested, in which case the check is performed when the
+/// outermost call returns.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{with_bulk_alloc, Gc};
+///
+/// let constants: Vec<Gc<u64>> = with_bulk_alloc(|| (0..10_000).map(Gc::new).collect());
+/// assert_eq!(*constants[42], 42);
+/// ```
+pub fn with_bulk_alloc<R>(f: impl FnOnce() -> R) -> R {
+    struct BulkGuard;
+    impl Drop for BulkGuard {
+        fn drop(&mut self) {
+            let _ = GC_STATE.try_with(|st| st.borrow_mut().bulk_alloc_depth -= 1);
+        }
+    }
+
+    GC_STATE.with(|st| st.borrow_mut().bulk_alloc_depth += 1);
+    let guard = BulkGuard;
+    let result = f();
+    drop(guard);
+
     GC_STATE.with(|st| {
         let mut st = st.borrow_mut();
+        if st.bulk_alloc_depth == 0 {
+            collect_if_needed(&mut st);
+        }
+    });
+    deliver_phases();
+    result
+}
 
-        // XXX We should probably be more clever about collecting
-        if st.stats.bytes_allocated > st.config.threshold {
-            collect_garbage(&mut st);
+// How many times `GcConfig::threshold` a `reserve` call can reserve.
+const MAX_RESERVED_THRESHOLDS: usize = 8;
 
-            if st.stats.bytes_allocated as f64
-                > st.config.threshold as f64 * st.config.used_space_ratio
-            {
-                // we didn't collect enough, so increase the
-                // threshold for next time, to avoid thrashing the
-                // collector too much/behaving quadratically.
-                st.config.threshold =
-                    (st.stats.bytes_allocated as f64 / st.config.used_space_ratio) as usize;
+/// Lets the next `additional` bytes allocated on the current thread's heap
+/// be allocated without triggering a garbage collection.
+///
+/// This is meant to be called before making allocations which will all stay
+/// alive, e.g. before deserializing a large document, so that the collector
+/// doesn't run while they are made, only to find nothing to free. Unlike
+/// `with_bulk_alloc`, the reservation isn't tied to a closure. It ends once
+/// `additional` more bytes are allocated or any collection runs. Calling it
+/// again while a reservation is active extends it if the new one reaches
+/// further.
+///
+/// `additional` is capped at eight times `GcConfig::threshold`, so that a
+/// large reservation can't turn automatic collection off until the next
+/// manual one.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{reserve, Gc};
+///
+/// reserve(1 << 20);
+/// let values: Vec<Gc<u64>> = (0..1000).map(Gc::new).collect();
+/// assert_eq!(*values[42], 42);
+/// ```
+pub fn reserve(additional: usize) {
+    GC_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        let additional =
+            additional.min(st.config.threshold.saturating_mul(MAX_RESERVED_THRESHOLDS));
+        let until = st.stats.bytes_allocated.saturating_add(additional);
+        st.reserved_until = st.reserved_until.max(until);
+    });
+}
+
+/// The address of a `Gc` allocation.
+///
+/// All `Gc`s pointing to the same allocation have the same `GcPointer`,
+/// which makes it usable to identify objects when inspecting the heap. Its
+/// representation is unspecified, and may change along with the layout of
+/// the allocations: it's only meant to be compared, hashed and printed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GcPointer(NonNull<GcBoxHeader>);
+
+impl GcPointer {
+    pub(crate) fn new<T: ?Sized>(gcbox: NonNull<GcBox<T>>) -> Self {
+        // The header is at the start of the `GcBox`, as it is repr(C).
+        GcPointer(gcbox.cast())
+    }
+
+    /// Returns the address of the allocation.
+    #[must_use]
+    pub fn addr(self) -> usize {
+        self.0.as_ptr() as usize
+    }
+
+    /// Returns the `GcPointer` of the allocation whose value is at `ptr`, as
+    /// returned by [`Gc::as_ptr`](crate::Gc::as_ptr).
+    ///
+    /// If `ptr` doesn't point to the value of a `Gc` allocation, the result
+    /// doesn't identify any allocation. The collector ignores such pointers
+    /// when they are reported by a [root source](register_root_source).
+    #[must_use]
+    pub fn from_raw<T>(ptr: *const T) -> Self {
+        // This relies on GcBox being #[repr(C)].
+        let (_, offset) = Layout::new::<GcBoxHeader>()
+            .extend(Layout::new::<T>())
+            .unwrap();
+        let header = ptr.cast::<u8>().wrapping_sub(offset) as *mut GcBoxHeader;
+        GcPointer(NonNull::new(header).unwrap_or(NonNull::dangling()))
+    }
+}
+
+/// Registers a function reporting allocations which must be treated as
+/// roots, for the current thread.
+///
+/// This is for allocations referenced from outside of any `Gc` through
+/// something the collector can't trace, such as tagged pointers encoded in
+/// an integer. Each collection calls `source` while marking, with a function
+/// to call with the [`GcPointer`] of every such allocation. Those are kept
+/// alive, along with everything reachable from them, as if a `Gc` to them
+/// were rooted.
+///
+/// Pointers which don't identify an allocation are ignored, so reporting a
+/// stale pointer can only keep another allocation alive, rather than cause
+/// unsafety.
+///
+/// `source` must not use the collector, e.g. by allocating, dereferencing
+/// unrooted `Gc`s or registering another root source: it is called while
+/// the collector's state is borrowed, and doing so will panic.
+///
+/// Once a root source is registered, the allocations remaining when the
+/// thread exits are leaked, as the source may no longer be able to report
+/// what they are referenced by.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{force_collect, register_root_source, Gc, GcPointer};
+/// use std::cell::RefCell;
+///
+/// thread_local!(static STACK: RefCell<Vec<usize>> = RefCell::new(Vec::new()));
+///
+/// register_root_source(|report| {
+///     STACK.with(|stack| {
+///         for &tagged in stack.borrow().iter() {
+///             if tagged & 1 == 1 {
+///                 report(GcPointer::from_raw((tagged & !1) as *const u64));
+///             }
+///         }
+///     })
+/// });
+///
+/// let object = Gc::new(42u64);
+/// let tagged = Gc::as_ptr(&object) as usize | 1;
+/// STACK.with(|stack| stack.borrow_mut().push(tagged));
+/// drop(object);
+///
+/// force_collect();
+/// assert_eq!(unsafe { *((tagged & !1) as *const u64) }, 42);
+/// ```
+pub fn register_root_source(source: impl Fn(&mut dyn FnMut(GcPointer)) + 'static) {
+    GC_STATE.with(|st| st.borrow_mut().root_sources.push(Box::new(source)));
+}
+
+/// A phase of a collection, as reported to the listener set with
+/// [`set_phase_listener`].
+///
+/// If any finalizers ran, the collector marks a second time to find the
+/// objects they resurrected, so a collection may report `MarkStart` and
+/// `MarkEnd` twice, after `FinalizeEnd`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GcPhase {
+    /// Marking the reachable objects has started.
+    MarkStart,
+    /// Marking has finished, or was stopped by [`try_collect`].
+    MarkEnd,
+    /// Finalizing the unreachable objects has started.
+    FinalizeStart,
+    /// Finalizing has finished.
+    FinalizeEnd,
+    /// Freeing the unreachable objects has started.
+    SweepStart,
+    /// Freeing has finished, and the collection is done.
+    SweepEnd,
+}
+
+type PhaseListener = Box<dyn FnMut(GcPhase)>;
+
+thread_local!(static PHASE_LISTENER: RefCell<Option<PhaseListener>> = const { RefCell::new(None) });
+
+// The phases entered and left by collections, which are kept until the
+// collection has released the state's borrow, and then delivered to the
+// listener. Set while they're being delivered.
+thread_local!(static PENDING_PHASES: RefCell<VecDeque<GcPhase>> = const { RefCell::new(VecDeque::new()) });
+thread_local!(static DELIVERING_PHASES: Cell<bool> = const { Cell::new(false) });
+
+/// Sets a function to be called with each [`GcPhase`] that collections on
+/// this thread enter and leave, replacing any previous one. This shows a
+/// profiler which phases each collection went through, e.g. whether it had
+/// to mark a second time after running finalizers.
+///
+/// The listener isn't called while the collection is in progress, but with
+/// each of its phases in order once it's done, so it may allocate `Gc`s, read
+/// the statistics or collect garbage. The phases of a collection it runs are
+/// delivered after those of the current one. It is dropped without being
+/// called as the thread exits.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{force_collect, set_phase_listener, GcPhase};
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let phases = Rc::new(RefCell::new(Vec::new()));
+/// let log = phases.clone();
+/// set_phase_listener(move |phase| log.borrow_mut().push(phase));
+///
+/// force_collect();
+/// assert_eq!(
+///     *phases.borrow(),
+///     [
+///         GcPhase::MarkStart,
+///         GcPhase::MarkEnd,
+///         GcPhase::FinalizeStart,
+///         GcPhase::FinalizeEnd,
+///         GcPhase::SweepStart,
+///         GcPhase::SweepEnd,
+///     ]
+/// );
+/// ```
+pub fn set_phase_listener(listener: impl FnMut(GcPhase) + 'static) {
+    PHASE_LISTENER.with(|l| *l.borrow_mut() = Some(Box::new(listener)));
+}
+
+fn fire_phase(phase: GcPhase) {
+    let _ = PENDING_PHASES.try_with(|p| p.borrow_mut().push_back(phase));
+}
+
+/// Calls the listener with the phases fired since this was last called.
+/// This must only be called once the collections which fired them have
+/// released the state's borrow.
+fn deliver_phases() {
+    // Drops the undelivered phases if the listener panics.
+    struct Delivering;
+    impl Drop for Delivering {
+        fn drop(&mut self) {
+            let _ = PENDING_PHASES.try_with(|p| p.borrow_mut().clear());
+            let _ = DELIVERING_PHASES.try_with(|d| d.set(false));
+        }
+    }
+
+    // The phases of a collection run by the listener are delivered by the
+    // outer call.
+    if DELIVERING_PHASES
+        .try_with(|d| d.replace(true))
+        .unwrap_or(true)
+    {
+        return;
+    }
+    let _delivering = Delivering;
+    while let Some(phase) = PENDING_PHASES.with(|p| p.borrow_mut().pop_front()) {
+        // Take the listener out while calling it, so it may replace itself.
+        let listener = PHASE_LISTENER.with(|l| l.borrow_mut().take());
+        if let Some(mut listener) = listener {
+            listener(phase);
+            PHASE_LISTENER.with(|l| {
+                l.borrow_mut().get_or_insert(listener);
+            });
+        }
+    }
+}
+
+impl fmt::Debug for GcPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.0, f)
+    }
+}
+
+// Whether `record_edges` is recording, or `collect_step` is marking, in which
+// case tracing a `Gc` doesn't mark through its `GcBox`. This is checked
+// before `RECORDED_EDGES` and `INCREMENTAL`, so that tracing a `Gc` is cheap
+// while neither is in use.
+const RECORDING: u8 = 1;
+const INCREMENTAL_MARKING: u8 = 2;
+thread_local!(static TRACE_MODE: Cell<u8> = const { Cell::new(0) });
+
+fn set_trace_mode(mode: u8, on: bool) {
+    let _ = TRACE_MODE.try_with(|m| m.set(if on { m.get() | mode } else { m.get() & !mode }));
+}
+
+/// Returns whether a `Gc` being traced must first be offered to
+/// `record_edge` and `defer_trace`.
+#[inline]
+pub(crate) fn trace_redirected() -> bool {
+    TRACE_MODE.try_with(|m| m.get() != 0).unwrap_or(false)
+}
+
+// While tracing for `Trace::visit_children`, `Gc`s record their pointer here
+// rather than marking their `GcBox`.
+thread_local!(static RECORDED_EDGES: RefCell<Option<Vec<GcPointer>>> = const { RefCell::new(None) });
+
+/// Records `ptr` as an outgoing edge if `record_edges` is collecting edges,
+/// returning whether it did so.
+pub(crate) fn record_edge(ptr: GcPointer) -> bool {
+    RECORDED_EDGES.with(|edges| match &mut *edges.borrow_mut() {
+        Some(edges) => {
+            edges.push(ptr);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Runs `trace`, returning the `Gc`s it traced rather than marking them.
+pub(crate) fn record_edges(trace: impl FnOnce()) -> Vec<GcPointer> {
+    // Restores the previous recording even if `trace` panics, so that
+    // collections don't keep recording instead of marking.
+    struct Restore(Option<Vec<GcPointer>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let outer = self.0.take();
+            set_trace_mode(RECORDING, outer.is_some());
+            let _ = RECORDED_EDGES.try_with(|edges| *edges.borrow_mut() = outer);
+        }
+    }
+
+    let restore = Restore(RECORDED_EDGES.with(|edges| edges.replace(Some(Vec::new()))));
+    set_trace_mode(RECORDING, true);
+    trace();
+    let edges = RECORDED_EDGES.with(|edges| edges.borrow_mut().take());
+    drop(restore);
+    edges.unwrap_or_default()
+}
+
+/// Returns whether any `GcBox` on the heap holds a `Gc` pointing to `ptr`.
+///
+/// If a collection is in progress the heap can't be traced, and `true` is
+/// returned.
+fn is_referenced(ptr: GcPointer) -> bool {
+    GC_STATE.with(|st| {
+        // Hold the state mutably so that tracing can't trigger a collection.
+        let st = match st.try_borrow_mut() {
+            Ok(st) => st,
+            Err(_) => return true,
+        };
+        let mut head = st.boxes_start.get();
+        while let Some(node) = head {
+            let gcbox = unsafe { node.as_ref() };
+
+            let mut found = false;
+            unsafe { gcbox.visit_data(&mut |child| found |= child == ptr) };
+            if found {
+                return true;
             }
+            head = gcbox.header.next.get();
         }
+        false
+    })
+}
+
+// The progress of an incremental collection started by `collect_step`.
+struct IncrementalMark {
+    // Marked boxes whose data hasn't been traced yet.
+    worklist: Vec<GcPointer>,
+    // Every box in the chain, to find the `dyn Trace` data of a worklist
+    // entry. Boxes allocated during the collection are added as well.
+    boxes: HashMap<GcPointer, NonNull<GcBox<dyn Trace>>>,
+}
+
+// This lives outside of `GcState` because `Gc`s push onto the worklist while
+// the data of a box is traced, and `GcCell`s do in their write barrier, both
+// of which can happen while the state is borrowed.
+thread_local!(static INCREMENTAL: RefCell<Option<IncrementalMark>> = const { RefCell::new(None) });
+
+/// Marks `ptr` and pushes it onto the worklist if an incremental collection
+/// is in progress, returning whether it did so. The caller must then not
+/// trace through `ptr` itself.
+pub(crate) unsafe fn defer_trace(ptr: GcPointer) -> bool {
+    INCREMENTAL
+        .try_with(|inc| match &mut *inc.borrow_mut() {
+            // Boxes of `GcRegion`s aren't part of the collection.
+            Some(inc) if !inc.boxes.contains_key(&ptr) => true,
+            Some(inc) => {
+                let header = ptr.0.as_ref();
+                if !header.is_marked() {
+                    header.mark();
+                    inc.worklist.push(ptr);
+                }
+                true
+            }
+            None => false,
+        })
+        .unwrap_or(false)
+}
+
+/// Called when a mutable borrow of a `GcCell` on the heap ends.
+///
+/// The `GcCell` may belong to a box which an incremental collection has
+/// already traced, so the `Gc`s that were stored in it meanwhile are
+/// marked and pushed onto the worklist. Likewise, it may belong to an old
+/// box, so the young boxes stored in it are remembered for minor collections.
+pub(crate) unsafe fn write_barrier<T: Trace + ?Sized>(value: &T) {
+    let active = INCREMENTAL
+        .try_with(|inc| inc.borrow().is_some())
+        .unwrap_or(false);
+    if active {
+        value.trace();
+    }
+    remember_young(value);
+}
 
-        let next = st.boxes_start.replace(gcbox);
-        gcbox.as_ref().header.next.set(next);
+// The young boxes which `Gc`s stored on the heap have pointed to since the
+// last collection. It is `None` until the first `minor_collect`, as keeping
+// it up to date makes the write barrier visit the mutated data.
+thread_local!(static REMEMBERED: RefCell<Option<HashSet<GcPointer>>> = const { RefCell::new(None) });
 
-        // We allocated some bytes! Let's record it
-        st.stats.bytes_allocated += mem::size_of_val::<GcBox<_>>(gcbox.as_ref());
+// Whether a minor collection is marking, in which case old boxes aren't
+// traced through.
+thread_local!(static MINOR_MARK: Cell<bool> = const { Cell::new(false) });
+
+/// Records the young boxes `value` points to, so that a minor collection
+/// keeps them alive even if they are only reachable from old boxes.
+unsafe fn remember_young<T: Trace + ?Sized>(value: &T) {
+    let _ = REMEMBERED.try_with(|rem| {
+        if let Some(rem) = &mut *rem.borrow_mut() {
+            value.visit_children(&mut |child| {
+                if !child.0.as_ref().is_old() {
+                    rem.insert(child);
+                }
+            });
+        }
     });
 }
 
+/// Abandons the incremental collection in progress, if any, so that a full
+/// collection can start from a clean slate.
+unsafe fn abandon_incremental_mark(head: Option<NonNull<GcBox<dyn Trace>>>) {
+    // If the thread-local is already gone, we can't tell whether boxes were
+    // left marked.
+    let active = INCREMENTAL
+        .try_with(|inc| inc.borrow_mut().take().is_some())
+        .unwrap_or(true);
+    set_trace_mode(INCREMENTAL_MARKING, false);
+    if active {
+        let mut head = head;
+        while let Some(node) = head {
+            node.as_ref().header.unmark();
+            head = node.as_ref().header.next.get();
+        }
+    }
+}
+
 impl<T: ?Sized> GcBox<T> {
     /// Returns `true` if the two references refer to the same `GcBox`.
     pub(crate) fn ptr_eq(this: &GcBox<T>, other: &GcBox<T>) -> bool {
@@ -227,13 +1206,47 @@ impl<T: ?Sized> GcBox<T> {
 }
 
 impl<T: Trace + ?Sized> GcBox<T> {
-    /// Marks this `GcBox` and marks through its data.
+    /// Marks this `GcBox` and marks through its data. Old boxes are left
+    /// alone during a minor collection.
     pub(crate) unsafe fn trace_inner(&self) {
-        if !self.header.is_marked() {
+        let skip = self.header.is_old() && MINOR_MARK.with(Cell::get);
+        if !self.header.is_marked() && !skip {
             self.header.mark();
+            self.trace_data();
+        }
+    }
+
+    /// Marks through this `GcBox`'s data, unless it is mutably borrowed by
+    /// `Gc::make_mut_scanning`, in which case its `Gc`s are rooted, or
+    /// uninitialized.
+    unsafe fn trace_data(&self) {
+        if !self.header.is_borrowed() && !self.header.is_uninit() {
             self.data.trace();
         }
     }
+
+    /// Visits the `Gc`s in this `GcBox`'s data, unless it is mutably
+    /// borrowed by `Gc::make_mut_scanning` or uninitialized.
+    unsafe fn visit_data(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        if !self.header.is_borrowed() && !self.header.is_uninit() {
+            self.data.visit_children(visitor);
+        }
+    }
+
+    /// Returns `true` if a single rooted `Gc` points to this `GcBox`, and
+    /// no other `GcBox` on the heap does.
+    pub(crate) fn is_unique(&self) -> bool {
+        self.header.roots() == 1 && !is_referenced(GcPointer::new(NonNull::from(self)))
+    }
+
+    /// Marks this `GcBox`'s data as mutably borrowed, or no longer borrowed.
+    ///
+    /// # Safety
+    ///
+    /// The `Gc`s in the data must be rooted while it is borrowed.
+    pub(crate) unsafe fn set_borrowed(&self, borrowed: bool) {
+        self.header.set_borrowed(borrowed);
+    }
 }
 
 impl<T: ?Sized> GcBox<T> {
@@ -249,6 +1262,18 @@ impl<T: ?Sized> GcBox<T> {
         self.header.dec_roots();
     }
 
+    /// Returns the root count of this `GcBox`.
+    #[cfg(feature = "unstable-stats")]
+    pub(crate) fn root_count(&self) -> usize {
+        self.header.roots()
+    }
+
+    /// Returns whether this `GcBox` is marked by the current collection.
+    #[cfg(feature = "unstable-stats")]
+    pub(crate) fn is_marked(&self) -> bool {
+        self.header.is_marked()
+    }
+
     /// Returns a pointer to the `GcBox`'s value, without dereferencing it.
     pub(crate) fn value_ptr(this: *const GcBox<T>) -> *const T {
         unsafe { ptr::addr_of!((*this).data) }
@@ -260,30 +1285,110 @@ impl<T: ?Sized> GcBox<T> {
     }
 }
 
+// Sets `MINOR_MARK` while a minor collection marks.
+struct MinorMark;
+impl MinorMark {
+    fn start(minor: bool) -> Option<MinorMark> {
+        minor.then(|| {
+            MINOR_MARK.with(|m| m.set(true));
+            MinorMark
+        })
+    }
+}
+impl Drop for MinorMark {
+    fn drop(&mut self) {
+        MINOR_MARK.with(|m| m.set(false));
+    }
+}
+
+// Marks the boxes reachable from the roots, among those before `until`.
+// Returns `false` if `should_continue` stopped it before it was done, leaving
+// some boxes marked.
+unsafe fn mark(
+    head: &Link,
+    until: Option<GcPointer>,
+    sources: &[RootSource],
+    minor: bool,
+    should_continue: &dyn Fn() -> bool,
+) -> bool {
+    let mut external = HashSet::new();
+    for source in sources {
+        source(&mut |ptr| {
+            external.insert(ptr);
+        });
+    }
+    if minor {
+        // Old boxes aren't traced, so the young boxes they point to
+        // are roots.
+        REMEMBERED.with(|rem| external.extend(rem.borrow().iter().flatten().copied()));
+    }
+    let _minor = MinorMark::start(minor);
+
+    // Walk the tree, tracing and marking the nodes
+    let mut mark_head = head.get();
+    while let Some(node) = mark_head {
+        if until == Some(GcPointer::new(node)) {
+            break;
+        }
+        if node.as_ref().header.roots() > 0 || external.contains(&GcPointer::new(node)) {
+            if !should_continue() {
+                return false;
+            }
+            node.as_ref().trace_inner();
+        }
+
+        mark_head = node.as_ref().header.next.get();
+    }
+    true
+}
+
 /// Collects garbage.
-fn collect_garbage(st: &mut GcState) {
+///
+/// A minor collection only frees young boxes. They are all at the start of
+/// the chain, as new boxes are pushed onto the front of it and every box
+/// which survives a collection is promoted.
+fn collect_garbage(st: &mut GcState, minor: bool) -> CollectOutcome {
+    collect_garbage_polled(st, minor, &|| true).expect("collection can't be stopped")
+}
+
+/// Collects garbage, unless `should_continue` returns `false` while marking,
+/// in which case the marks are undone and `None` is returned.
+fn collect_garbage_polled(
+    st: &mut GcState,
+    minor: bool,
+    should_continue: &dyn Fn() -> bool,
+) -> Option<CollectOutcome> {
     struct Unmarked<'a> {
-        incoming: &'a Cell<Option<NonNull<GcBox<dyn Trace>>>>,
+        incoming: &'a Link,
         this: NonNull<GcBox<dyn Trace>>,
     }
-    unsafe fn mark(head: &Cell<Option<NonNull<GcBox<dyn Trace>>>>) -> Vec<Unmarked<'_>> {
-        // Walk the tree, tracing and marking the nodes
-        let mut mark_head = head.get();
-        while let Some(node) = mark_head {
-            if node.as_ref().header.roots() > 0 {
-                node.as_ref().trace_inner();
+    unsafe fn first_old(head: &Link) -> Option<GcPointer> {
+        let mut head = head.get();
+        while let Some(node) = head {
+            if node.as_ref().header.is_old() {
+                return Some(GcPointer::new(node));
             }
-
-            mark_head = node.as_ref().header.next.get();
+            head = node.as_ref().header.next.get();
         }
+        None
+    }
 
+    // Returns the unmarked boxes before `until`. If `promote` is set, the
+    // marked ones are unmarked and promoted, otherwise they are left alone.
+    unsafe fn unmark(head: &Link, until: Option<GcPointer>, promote: bool) -> Vec<Unmarked<'_>> {
         // Collect a vector of all of the nodes which were not marked,
         // and unmark the ones which were.
         let mut unmarked = Vec::new();
         let mut unmark_head = head;
         while let Some(node) = unmark_head.get() {
+            if until == Some(GcPointer::new(node)) {
+                break;
+            }
             if node.as_ref().header.is_marked() {
-                node.as_ref().header.unmark();
+                if promote {
+                    node.as_ref().header.unmark();
+                    node.as_ref().header.set_old();
+                }
             } else {
                 unmarked.push(Unmarked {
                     incoming: unmark_head,
@@ -295,47 +1400,545 @@ fn collect_garbage(st: &mut GcState) {
         unmarked
     }
 
-    unsafe fn sweep(finalized: Vec<Unmarked<'_>>, bytes_allocated: &mut usize) {
+    unsafe fn sweep(
+        finalized: Vec<Unmarked<'_>>,
+        bytes_allocated: &mut usize,
+        allocator: &Allocator,
+        refinalize_resurrected: bool,
+    ) -> usize {
         let _guard = DropGuard::new();
+        let mut freed = 0;
         for node in finalized.into_iter().rev() {
-            if node.this.as_ref().header.is_marked() {
+            let header = &node.this.as_ref().header;
+            if header.is_marked() {
+                // Resurrected by a finalizer.
+                if refinalize_resurrected {
+                    header.set_finalized(false);
+                }
                 continue;
             }
             let incoming = node.incoming;
-            let node = Box::from_raw(node.this.as_ptr());
-            *bytes_allocated -= mem::size_of_val::<GcBox<_>>(&*node);
-            incoming.set(node.header.next.take());
+            let node = node.this;
+            let layout = node.as_ref().layout();
+            *bytes_allocated -= layout.size();
+            incoming.set(node.as_ref().header.next.take());
+            if !node.as_ref().header.is_uninit() {
+                ptr::drop_in_place(node.as_ptr());
+            }
+            allocator.dealloc(node.as_ptr().cast::<u8>(), layout);
+            freed += 1;
+        }
+        freed
+    }
+
+    // Unmarks every box if a finalizer, `Trace` or `Drop` impl panics, so
+    // that the boxes left marked can't stop the next collection from tracing
+    // through them. The chain itself stays valid, as the sweep unlinks each
+    // box before dropping it.
+    struct UnmarkOnUnwind<'a>(&'a Link);
+    impl Drop for UnmarkOnUnwind<'_> {
+        fn drop(&mut self) {
+            let mut head = self.0.get();
+            while let Some(node) = head {
+                let header = unsafe { &node.as_ref().header };
+                header.unmark();
+                head = header.next.get();
+            }
+        }
+    }
+
+    // Restores the incremental collection set aside while collecting a
+    // region, which can't reference the thread's heap.
+    struct Resume(Option<IncrementalMark>);
+    impl Drop for Resume {
+        fn drop(&mut self) {
+            if let Some(inc) = self.0.take() {
+                let _ = INCREMENTAL.try_with(|i| *i.borrow_mut() = Some(inc));
+                set_trace_mode(INCREMENTAL_MARKING, true);
+            }
+        }
+    }
+
+    let _resume = if st.region {
+        let inc = INCREMENTAL
+            .try_with(|inc| inc.borrow_mut().take())
+            .unwrap_or(None);
+        set_trace_mode(INCREMENTAL_MARKING, false);
+        Resume(inc)
+    } else {
+        unsafe { abandon_incremental_mark(st.boxes_start.get()) };
+        Resume(None)
+    };
+
+    let mut outcome = CollectOutcome {
+        bytes_before: st.stats.bytes_allocated,
+        bytes_after: st.stats.bytes_allocated,
+        objects_freed: 0,
+    };
+
+    // A minor collection only unmarks and promotes its survivors once it's
+    // done, so that marking again after finalizers still stops at the same
+    // box.
+    let until = if minor {
+        unsafe { first_old(&st.boxes_start) }
+    } else {
+        None
+    };
+    fire_phase(GcPhase::MarkStart);
+    let unmark_on_unwind = UnmarkOnUnwind(&st.boxes_start);
+    let marked = unsafe {
+        mark(
+            &st.boxes_start,
+            until,
+            &st.root_sources,
+            minor,
+            should_continue,
+        )
+    };
+    fire_phase(GcPhase::MarkEnd);
+    mem::forget(unmark_on_unwind);
+    if !marked {
+        // Nothing but the marks has changed yet.
+        let mut head = st.boxes_start.get();
+        while let Some(node) = head {
+            let header = unsafe { &node.as_ref().header };
+            header.unmark();
+            head = header.next.get();
         }
+        return None;
     }
 
     st.stats.collections_performed += 1;
+    st.allocations = 0;
+    st.reserved_until = 0;
+    #[cfg(all(feature = "unstable-stats", debug_assertions))]
+    count_roots(st);
 
     unsafe {
-        let head = Cell::from_mut(&mut st.boxes_start);
-        let unmarked = mark(head);
-        if unmarked.is_empty() {
-            return;
-        }
+        let head = &st.boxes_start;
+        let unmark_on_unwind = UnmarkOnUnwind(head);
+        let unmarked = unmark(head, until, !minor);
+        // New boxes are pushed onto the front of the chain, so this
+        // finalizes in reverse allocation order, as documented on `Finalize`.
+        let mut finalized_any = false;
+        fire_phase(GcPhase::FinalizeStart);
         for node in &unmarked {
-            Trace::finalize_glue(&node.this.as_ref().data);
+            let gcbox = node.this.as_ref();
+            if !gcbox.header.is_finalized()
+                && !gcbox.header.is_uninit()
+                && gcbox.data.needs_finalize()
+            {
+                gcbox.header.set_finalized(true);
+                Trace::finalize_glue(&gcbox.data);
+                finalized_any = true;
+            }
+        }
+        // Finalizers may have resurrected some of the unmarked objects by
+        // making them reachable again, so mark again, and keep the marks
+        // until the sweep has skipped them. Only finalizers can resurrect
+        // objects, so this can be skipped if none ran.
+        fire_phase(GcPhase::FinalizeEnd);
+        if finalized_any {
+            fire_phase(GcPhase::MarkStart);
+            mark(head, until, &st.root_sources, minor, &|| true);
+            fire_phase(GcPhase::MarkEnd);
+        }
+        fire_phase(GcPhase::SweepStart);
+        outcome.objects_freed = sweep(
+            unmarked,
+            &mut st.stats.bytes_allocated,
+            &st.allocator,
+            st.config.refinalize_resurrected,
+        );
+        if finalized_any || minor {
+            unmark(head, until, true);
+        }
+        mem::forget(unmark_on_unwind);
+        fire_phase(GcPhase::SweepEnd);
+    }
+
+    // Every box left is old now.
+    if !st.region {
+        let _ = REMEMBERED.try_with(|rem| {
+            if let Some(rem) = &mut *rem.borrow_mut() {
+                rem.clear();
+            }
+        });
+    }
+
+    outcome.bytes_after = st.stats.bytes_allocated;
+    Some(outcome)
+}
+
+/// An error returned by [`Gc::try_new`](struct.Gc.html#method.try_new).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Default, Hash)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt("memory allocation failed", f)
+    }
+}
+
+/// A separate garbage-collected heap, collected independently of the rest
+/// of the thread's `Gc`s.
+///
+/// Collecting the whole thread's heap is wasteful when it holds several
+/// independent graphs, such as one per request handled by a server. Each
+/// graph can instead be allocated in its own region, with its own
+/// allocation threshold and statistics, and `collect` only traces that
+/// region.
+///
+/// Regions must not reference each other or the thread's heap: see the
+/// safety section of [`GcRegion::alloc`]. Dropping a region collects it, and
+/// leaks any allocations which are still rooted.
+pub struct GcRegion {
+    state: RefCell<GcState>,
+}
+
+impl GcRegion {
+    /// Creates an empty region.
+    #[must_use]
+    pub fn new() -> Self {
+        GcRegion {
+            state: RefCell::new(GcState::new(true)),
         }
-        mark(head);
-        sweep(unmarked, &mut st.stats.bytes_allocated);
     }
+
+    /// Constructs a new `Gc<T>` with the given value, in this region.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection of this region.
+    ///
+    /// # Safety
+    ///
+    /// The collector only traces a region's allocations when collecting that
+    /// region, and the thread's other allocations when collecting those. So
+    /// `value` must only contain `Gc`s allocated in this region, and the
+    /// returned `Gc` must only be stored on the stack, or in allocations of
+    /// this region. `Gc::make_mut_scanning` must not be used on it either, as
+    /// it would allocate the copy on the thread's heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{Gc, GcRegion};
+    ///
+    /// let region = GcRegion::new();
+    /// let five = unsafe { region.alloc(5) };
+    /// let list = unsafe { region.alloc(vec![five.clone()]) };
+    /// drop(list);
+    /// assert_eq!(region.collect().objects_freed, 1);
+    /// ```
+    pub unsafe fn alloc<T: Trace>(&self, value: T) -> crate::Gc<T> {
+        crate::Gc::from_gcbox(GcBox::new_in(value, &self.state))
+    }
+
+    /// Immediately collects the garbage in this region, and returns how much
+    /// it reclaimed.
+    ///
+    /// This will panic if executed while a collection of this region is
+    /// currently in progress
+    pub fn collect(&self) -> CollectOutcome {
+        let outcome = collect_garbage(&mut self.state.borrow_mut(), false);
+        deliver_phases();
+        outcome
+    }
+}
+
+impl Default for GcRegion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a single garbage collection reclaimed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CollectOutcome {
+    /// The number of bytes allocated before the collection.
+    pub bytes_before: usize,
+    /// The number of bytes still allocated after the collection.
+    pub bytes_after: usize,
+    /// The number of `Gc` allocations which were freed.
+    pub objects_freed: usize,
 }
 
 /// Immediately triggers a garbage collection on the current thread.
 ///
-/// This will panic if executed while a collection is currently in progress
+/// If a collection is already in progress, e.g. when called from a finalizer,
+/// this does nothing. Use `try_collect(|| true)` to find out whether a
+/// collection was performed.
 pub fn force_collect() {
     GC_STATE.with(|st| {
+        if let Ok(mut st) = st.try_borrow_mut() {
+            collect_garbage(&mut st, false);
+        }
+    });
+    deliver_phases();
+}
+
+/// Triggers a garbage collection on the current thread, which can be called
+/// off while marking.
+///
+/// `should_continue` is called before tracing from each root. Once it
+/// returns `false`, the marks made so far are undone and nothing is
+/// finalized or freed, leaving the heap as it was. This lets a collection be
+/// abandoned, e.g. when a frame deadline is approaching; see also
+/// `collect_step`, which splits a collection into bounded steps instead.
+/// `should_continue` must not allocate or collect.
+///
+/// Returns whether a collection was performed. If a collection is already in
+/// progress, e.g. when called from a finalizer, this does nothing and returns
+/// `false`.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{try_collect, Gc};
+///
+/// let _root = Gc::new(1);
+/// assert!(!try_collect(|| false));
+/// assert!(try_collect(|| true));
+/// ```
+pub fn try_collect(should_continue: impl Fn() -> bool) -> bool {
+    let collected = GC_STATE.with(|st| match st.try_borrow_mut() {
+        Ok(mut st) => collect_garbage_polled(&mut st, false, &should_continue).is_some(),
+        Err(_) => false,
+    });
+    deliver_phases();
+    collected
+}
+
+/// Immediately triggers a minor garbage collection on the current thread,
+/// which only frees allocations made since the previous collection.
+///
+/// Most allocations in a typical program become unreachable soon after
+/// they are made. Every allocation which survives a collection is promoted,
+/// and a minor collection neither traces through nor frees promoted
+/// allocations, so it only takes time in proportion to the young ones. The
+/// promoted allocations which became unreachable are left for the next full
+/// collection, e.g. by `force_collect` or by allocating.
+///
+/// To find the young allocations which are only reachable from old ones,
+/// ending a mutable borrow of a `GcCell` on the heap records the young
+/// allocations it points to once minor collections are in use. This visits
+/// the `GcCell`'s contents, so keeping large collections in one `GcCell`
+/// makes mutating them slower. The first call performs a full collection,
+/// as nothing was recorded before it.
+///
+/// If a collection is already in progress, e.g. when called from a finalizer,
+/// this does nothing and returns `false`.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{force_collect_reporting, minor_collect, Gc};
+///
+/// let old = Gc::new(1);
+/// minor_collect();
+///
+/// drop(old);
+/// let _young = Gc::new(2);
+/// drop(Gc::new(3));
+/// // Frees the young `3`, but leaves the old `1` for a full collection.
+/// minor_collect();
+/// assert_eq!(force_collect_reporting().objects_freed, 1);
+/// ```
+pub fn minor_collect() -> bool {
+    let collected = GC_STATE.with(|st| match st.try_borrow_mut() {
+        Ok(mut st) => {
+            let recording = REMEMBERED.with(|rem| {
+                let mut rem = rem.borrow_mut();
+                let recording = rem.is_some();
+                rem.get_or_insert_with(HashSet::new);
+                recording
+            });
+            collect_garbage(&mut st, recording);
+            true
+        }
+        Err(_) => false,
+    });
+    deliver_phases();
+    collected
+}
+
+/// Collects garbage on the current thread, then lowers the allocation
+/// threshold to fit what is still allocated.
+///
+/// The threshold grows whenever a collection leaves the heap too full, but
+/// never shrinks on its own, so after a transient spike in allocations the
+/// collector would otherwise keep running rarely, letting a lot of garbage
+/// pile up. This resets it to what the remaining heap calls for, as set by
+/// `GcConfig::used_space_ratio`, but not below `GcConfig::min_threshold`.
+///
+/// If a collection is already in progress, e.g. when called from a finalizer,
+/// this does nothing, like `force_collect`.
+pub fn shrink_to_fit() {
+    GC_STATE.with(|st| {
+        if let Ok(mut st) = st.try_borrow_mut() {
+            collect_garbage(&mut st, false);
+            let fit = (st.stats.bytes_allocated as f64 / st.config.used_space_ratio) as usize;
+            let fit = fit.max(st.config.min_threshold);
+            st.config.threshold = st.config.threshold.min(fit);
+        }
+    });
+    deliver_phases();
+}
+
+/// Immediately triggers a garbage collection on the current thread, and
+/// returns how much it reclaimed.
+///
+/// This will panic if executed while a collection is currently in progress
+///
+/// # Examples
+///
+/// ```
+/// use gc::{force_collect_reporting, Gc};
+///
+/// drop(Gc::new(5));
+/// assert_eq!(force_collect_reporting().objects_freed, 1);
+/// assert_eq!(force_collect_reporting().objects_freed, 0);
+/// ```
+pub fn force_collect_reporting() -> CollectOutcome {
+    let outcome = GC_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        collect_garbage(&mut st, false)
+    });
+    deliver_phases();
+    outcome
+}
+
+/// Performs part of a garbage collection on the current thread, taking
+/// roughly at most `max`, and returns whether the collection completed.
+///
+/// This is meant for programs which can't afford to pause for a whole
+/// collection at once, such as games. The first call starts a collection,
+/// and each following call traces more of the heap, until everything
+/// reachable has been marked. The call which finds nothing left to trace
+/// finishes the collection and returns `true`; the next call starts a new
+/// one. `GcCell` mutations made between calls are tracked, so the program
+/// may keep using the heap freely in the meantime.
+///
+/// This is a simple, non-generational scheme, and has some limitations:
+///
+/// - Starting a collection walks the whole heap to find the roots.
+/// - Finishing it is not incremental: the roots are scanned again, the
+///   unreachable allocations finalized and freed all at once.
+/// - Allocations which become unreachable during a collection may only be
+///   freed by the next one.
+/// - A collection triggered by allocating, or by `force_collect`, while an
+///   incremental one is in progress discards its progress. Consider raising
+///   the allocation threshold when collecting incrementally.
+///
+/// This will panic if executed while a collection is currently in progress
+///
+/// # Examples
+///
+/// ```
+/// use gc::{collect_step, Gc};
+/// use std::time::Duration;
+///
+/// drop(Gc::new(5));
+/// while !collect_step(Duration::from_micros(100)) {
+///     // Do some other work.
+/// }
+/// ```
+pub fn collect_step(max: Duration) -> bool {
+    let deadline = Instant::now().checked_add(max);
+    let completed = GC_STATE.with(|st| {
         let mut st = st.borrow_mut();
-        collect_garbage(&mut st);
+
+        unsafe {
+            INCREMENTAL.with(|inc| {
+                let mut inc = inc.borrow_mut();
+                if inc.is_none() {
+                    let mut mark = IncrementalMark {
+                        worklist: Vec::new(),
+                        boxes: HashMap::new(),
+                    };
+                    let mut head = st.boxes_start.get();
+                    while let Some(node) = head {
+                        let ptr = GcPointer::new(node);
+                        mark.boxes.insert(ptr, node);
+                        if node.as_ref().header.roots() > 0 {
+                            node.as_ref().header.mark();
+                            mark.worklist.push(ptr);
+                        }
+                        head = node.as_ref().header.next.get();
+                    }
+                    *inc = Some(mark);
+                    set_trace_mode(INCREMENTAL_MARKING, true);
+                }
+            });
+
+            loop {
+                // Don't hold the borrow while tracing, as the data's `Gc`s
+                // push onto the worklist.
+                let next = INCREMENTAL.with(|inc| {
+                    let mut inc = inc.borrow_mut();
+                    let inc = inc.as_mut()?;
+                    let ptr = inc.worklist.pop()?;
+                    Some(inc.boxes[&ptr])
+                });
+                match next {
+                    Some(node) => node.as_ref().trace_data(),
+                    None => break,
+                }
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return false;
+                }
+            }
+
+            // Everything marked so far has been traced. Ending the
+            // incremental collection keeps those marks, so the full
+            // collection only has to trace what was rooted since.
+            INCREMENTAL.with(|inc| inc.borrow_mut().take());
+            set_trace_mode(INCREMENTAL_MARKING, false);
+        }
+
+        collect_garbage(&mut st, false);
+        true
+    });
+    deliver_phases();
+    completed
+}
+
+/// Runs the finalizers of every `Gc` allocation on the current thread,
+/// whether it is reachable or not.
+///
+/// This is meant for shutting down gracefully, as allocations which are
+/// still rooted when the thread exits, e.g. by other thread-locals, are
+/// leaked without being finalized. Each allocation is only ever finalized
+/// once by this function, and not again when it is later collected, so
+/// calling it repeatedly finalizes only the allocations made since.
+///
+/// This does not free any memory. Dereferencing a `Gc` which is not a root
+/// panics while the finalizers run, as the allocation it points to may
+/// already have been finalized.
+///
+/// This will panic if executed while a collection is currently in progress
+pub fn run_all_finalizers() {
+    GC_STATE.with(|st| {
+        let st = st.borrow_mut();
+        let _guard = DropGuard::new();
+
+        let mut head = st.boxes_start.get();
+        while let Some(node) = head {
+            let gcbox = unsafe { node.as_ref() };
+            if !gcbox.header.is_finalized() && !gcbox.header.is_uninit() {
+                gcbox.header.set_finalized(true);
+                Trace::finalize_glue(&gcbox.data);
+            }
+            head = gcbox.header.next.get();
+        }
     });
 }
 
 pub struct GcConfig {
     pub threshold: usize,
+    /// The lowest value `shrink_to_fit` lowers `threshold` to.
+    pub min_threshold: usize,
     /// after collection we want the the ratio of used/total to be no
     /// greater than this (the threshold grows exponentially, to avoid
     /// quadratic behavior when the heap is growing linearly with the
@@ -344,6 +1947,30 @@ pub struct GcConfig {
     /// For short-running processes it is not always appropriate to run
     /// GC, sometimes it is better to let system free the resources
     pub leak_on_drop: bool,
+    /// What makes an allocation trigger a collection.
+    pub trigger: CollectionTrigger,
+    /// Whether an object is finalized again when it becomes unreachable
+    /// after being resurrected.
+    ///
+    /// An object is resurrected when a finalizer makes it reachable again,
+    /// e.g. by storing a `Gc` to it in a rooted location. It then survives
+    /// the collection which finalized it. By default, each object is
+    /// finalized at most once, so a resurrected object is freed without
+    /// running its finalizer again when it later becomes unreachable. When
+    /// this is set, every collection which resurrects an object allows it
+    /// to be finalized again.
+    ///
+    /// Objects finalized by `run_all_finalizers` are only finalized again
+    /// if they are resurrected after that.
+    pub refinalize_resurrected: bool,
+    /// Whether allocating triggers collections, as set by `trigger`.
+    ///
+    /// When this is unset, the heap grows until `force_collect` or
+    /// `collect_step` is called, so that an embedder can schedule
+    /// collections itself, e.g. based on `stats`. Unlike `with_bulk_alloc`,
+    /// this lasts until it is set again. The heap is still collected when
+    /// the thread exits, unless `leak_on_drop` is set.
+    pub auto_collect: bool,
 }
 
 impl Default for GcConfig {
@@ -351,11 +1978,33 @@ impl Default for GcConfig {
         Self {
             used_space_ratio: 0.7,
             threshold: 100,
+            min_threshold: 100,
             leak_on_drop: false,
+            trigger: CollectionTrigger::Bytes,
+            refinalize_resurrected: false,
+            auto_collect: true,
         }
     }
 }
 
+/// When allocating triggers a collection, as configured in
+/// [`GcConfig::trigger`].
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollectionTrigger {
+    /// Collect once more than `GcConfig::threshold` bytes are allocated. The
+    /// threshold grows with the heap, as set by `used_space_ratio`.
+    ///
+    /// This is the default.
+    Bytes,
+    /// Collect once this many allocations were made since the last
+    /// collection, whatever their size. This suits many small objects, but
+    /// doesn't grow with the heap.
+    Allocations(usize),
+    /// Collect on whichever of `Bytes` and `Allocations` comes first.
+    Either(usize),
+}
+
 #[allow(dead_code)]
 pub fn configure(configurer: impl FnOnce(&mut GcConfig)) {
     GC_STATE.with(|st| {
@@ -364,10 +2013,171 @@ pub fn configure(configurer: impl FnOnce(&mut GcConfig)) {
     });
 }
 
+/// A single allocation in the output of [`dump_heap`].
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct HeapNode {
+    /// The address of the allocation.
+    pub address: GcPointer,
+    /// The size of the allocation in bytes, including its header.
+    pub size: usize,
+    /// The number of roots pointing to the allocation.
+    pub roots: usize,
+    /// Whether the allocation is reachable from a root, i.e. whether it
+    /// would survive a collection.
+    pub reachable: bool,
+    /// The allocations directly referenced by this one.
+    pub edges: Vec<GcPointer>,
+}
+
+/// Returns every allocation on the current thread's heap, along with the
+/// references between them.
+///
+/// This is meant for debugging retention: following `edges` back from an
+/// unexpectedly `reachable` node leads to the root which is keeping it alive.
+/// The references held by a mutably borrowed `GcCell` are missing from
+/// `edges`, as with [`Trace::visit_children`], but the allocations they point
+/// to are counted as rooted while the borrow lasts.
+///
+/// This will panic if executed while a collection is currently in progress
+#[allow(dead_code)]
+#[must_use]
+pub fn dump_heap() -> Vec<HeapNode> {
+    GC_STATE.with(|st| {
+        // Hold the state mutably so that tracing can't trigger a collection.
+        let st = st.borrow_mut();
+        let mut nodes = Vec::new();
+
+        unsafe {
+            abandon_incremental_mark(st.boxes_start.get());
+
+            let mut head = st.boxes_start.get();
+            while let Some(node) = head {
+                if node.as_ref().header.roots() > 0 {
+                    node.as_ref().trace_inner();
+                }
+                head = node.as_ref().header.next.get();
+            }
+
+            let mut head = st.boxes_start.get();
+            while let Some(node) = head {
+                let gcbox = node.as_ref();
+
+                let mut edges = Vec::new();
+                gcbox.visit_data(&mut |child| edges.push(child));
+
+                nodes.push(HeapNode {
+                    address: GcPointer::new(node),
+                    size: gcbox.layout().size(),
+                    roots: gcbox.header.roots(),
+                    reachable: gcbox.header.is_marked(),
+                    edges,
+                });
+                gcbox.header.unmark();
+                head = gcbox.header.next.get();
+            }
+        }
+
+        nodes
+    })
+}
+
+/// The allocations found by [`mark_only`].
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default)]
+pub struct MarkResult {
+    /// The allocations reachable from a root, which a collection would keep.
+    pub reachable: Vec<GcPointer>,
+    /// The allocations a collection would finalize and free, unless their
+    /// finalizers resurrect them.
+    pub unreachable: Vec<GcPointer>,
+}
+
+/// Runs the mark phase of a collection on the current thread's heap, and
+/// returns which allocations it reached, without sweeping anything.
+///
+/// Every allocation is unmarked again afterwards, so this leaves the heap as
+/// it was, apart from abandoning an incremental collection in progress.
+///
+/// This will panic if executed while a collection is currently in progress
+#[allow(dead_code)]
+#[must_use]
+pub fn mark_only() -> MarkResult {
+    GC_STATE.with(|st| {
+        // Hold the state mutably so that tracing can't trigger a collection.
+        let st = st.borrow_mut();
+        let mut result = MarkResult::default();
+
+        unsafe {
+            abandon_incremental_mark(st.boxes_start.get());
+            mark(&st.boxes_start, None, &st.root_sources, false, &|| true);
+
+            let mut head = st.boxes_start.get();
+            while let Some(node) = head {
+                let header = &node.as_ref().header;
+                if header.is_marked() {
+                    header.unmark();
+                    result.reachable.push(GcPointer::new(node));
+                } else {
+                    result.unreachable.push(GcPointer::new(node));
+                }
+                head = header.next.get();
+            }
+        }
+
+        result
+    })
+}
+
 #[derive(Clone, Default)]
 pub struct GcStats {
     pub bytes_allocated: usize,
     pub collections_performed: usize,
+    /// The number of rooted allocations at the last collection.
+    ///
+    /// This is only counted in debug builds, and stays 0 otherwise.
+    #[cfg(feature = "unstable-stats")]
+    pub rooted_objects: usize,
+    /// The number of allocations which were rooted at each of the last
+    /// `LEAK_COLLECTIONS` collections.
+    ///
+    /// Long-lived roots are counted too, but a number that keeps growing
+    /// usually means that `Gc`s are being leaked, e.g. with `mem::forget`.
+    /// Like `rooted_objects`, this is only counted in debug builds.
+    #[cfg(feature = "unstable-stats")]
+    pub leaked_roots: usize,
+    /// The highest `bytes_allocated` has been since the thread started, or
+    /// since the last call to [`reset_peak`].
+    #[cfg(feature = "unstable-stats")]
+    pub peak_bytes_allocated: usize,
+}
+
+/// The number of consecutive collections an allocation must be rooted at
+/// to be counted in [`GcStats::leaked_roots`].
+#[cfg(feature = "unstable-stats")]
+pub const LEAK_COLLECTIONS: usize = 3;
+
+/// Updates the root statistics at the start of a collection.
+#[cfg(all(feature = "unstable-stats", debug_assertions))]
+fn count_roots(st: &mut GcState) {
+    let mut rooted_for = HashMap::new();
+    let mut head = st.boxes_start.get();
+    while let Some(node) = head {
+        let header = unsafe { &node.as_ref().header };
+        if header.roots() > 0 {
+            let ptr = GcPointer::new(node);
+            let collections = st.rooted_for.get(&ptr).map_or(1, |n| n + 1);
+            rooted_for.insert(ptr, collections);
+        }
+        head = header.next.get();
+    }
+
+    st.stats.rooted_objects = rooted_for.len();
+    st.stats.leaked_roots = rooted_for
+        .values()
+        .filter(|&&n| n >= LEAK_COLLECTIONS)
+        .count();
+    st.rooted_for = rooted_for;
 }
 
 #[allow(dead_code)]
@@ -375,3 +2185,13 @@ pub struct GcStats {
 pub fn stats() -> GcStats {
     GC_STATE.with(|st| st.borrow().stats.clone())
 }
+
+/// Resets [`GcStats::peak_bytes_allocated`] to the current number of bytes
+/// allocated, e.g. to measure the peak after a warmup phase.
+#[cfg(feature = "unstable-stats")]
+pub fn reset_peak() {
+    GC_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        st.stats.peak_bytes_allocated = st.stats.bytes_allocated;
+    });
+}
@@ -9,15 +9,19 @@
     feature(coerce_unsized, dispatch_from_dyn, unsize)
 )]
 
-use crate::gc::{GcBox, GcBoxHeader};
+use crate::gc::{DstMeta, GcBox, GcBoxHeader};
 use std::alloc::Layout;
-use std::cell::{Cell, UnsafeCell};
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell, UnsafeCell};
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Display};
 use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::marker::PhantomData;
-use std::mem::{self, ManuallyDrop};
-use std::ops::{Deref, DerefMut};
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut, Index};
+use std::pin::Pin;
 use std::ptr::{self, NonNull};
 use std::rc::Rc;
 
@@ -36,13 +40,22 @@ pub use gc_derive::{Finalize, Trace};
 
 // We re-export the Trace method, as well as some useful internal methods for
 // managing collections or configuring the garbage collector.
-pub use crate::gc::{finalizer_safe, force_collect};
+pub use crate::gc::{
+    collect_step, finalizer_safe, force_collect, force_collect_reporting, minor_collect,
+    register_root_source, reserve, run_all_finalizers, set_allocator, set_phase_listener,
+    shrink_to_fit, try_collect, with_bulk_alloc, with_finalizer_access, AllocError, CollectOutcome,
+    GcAllocator, GcPhase, GcPointer, GcRegion,
+};
 pub use crate::trace::{Finalize, Trace};
 
 #[cfg(feature = "unstable-config")]
-pub use crate::gc::{configure, GcConfig};
+pub use crate::gc::{configure, CollectionTrigger, GcConfig};
 #[cfg(feature = "unstable-stats")]
-pub use crate::gc::{stats, GcStats};
+pub use crate::gc::{
+    dump_heap, mark_only, reset_peak, stats, GcStats, HeapNode, MarkResult, LEAK_COLLECTIONS,
+};
+#[cfg(feature = "serde")]
+pub use crate::serde::{with_sharing, SharedGc};
 
 ////////
 // Gc //
@@ -51,6 +64,13 @@ pub use crate::gc::{stats, GcStats};
 /// A garbage-collected pointer type over an immutable value.
 ///
 /// See the [module level documentation](./) for more details.
+///
+/// `Debug` prints `...` in place of a `Gc` that is already being printed, so
+/// cyclic structures can be printed. `PartialEq` compares the contents, as
+/// [`Gc::value_eq`] does, and terminates on cyclic structures too. `Hash`
+/// and `Ord` forward to the contents without such a check, and don't
+/// terminate on cyclic structures: use [`Gc::ptr_eq`], [`Gc::ptr_hash`] or
+/// [`GcPtrKey`] to compare and hash those by identity instead.
 pub struct Gc<T: ?Sized + 'static> {
     ptr_root: Cell<NonNull<GcBox<T>>>,
     marker: PhantomData<Rc<T>>,
@@ -80,230 +100,886 @@ impl<T: Trace> Gc<T> {
     pub fn new(value: T) -> Self {
         unsafe { Gc::from_gcbox(GcBox::new(value)) }
     }
-}
 
-impl<T: Trace + ?Sized> Gc<T> {
-    /// Constructs a `Gc` that points to a new `GcBox`.
+    /// Constructs a new `Gc<T>` with the given value, returning an error
+    /// instead of aborting if memory can't be allocated.
     ///
-    /// # Safety
+    /// If the allocation fails, garbage is collected and the allocation
+    /// retried once, before giving up.
     ///
-    /// `ptr` must point to a valid `GcBox` on the thread-local
-    /// `GcBox` chain.
-    #[inline]
-    unsafe fn from_gcbox(ptr: NonNull<GcBox<T>>) -> Gc<T> {
-        assert!(mem::align_of_val::<GcBox<T>>(ptr.as_ref()) > 1);
-
-        // When we create a Gc<T>, all pointers which have been moved to the
-        // heap no longer need to be rooted, so we unroot them.
-        ptr.as_ref().value().unroot();
-        let gc = Gc {
-            ptr_root: Cell::new(ptr),
-            marker: PhantomData,
-        };
-        gc.set_root();
-        gc
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let five = Gc::try_new(5).unwrap();
+    /// assert_eq!(*five, 5);
+    /// ```
+    pub fn try_new(value: T) -> Result<Self, AllocError> {
+        unsafe { Ok(Gc::from_gcbox(GcBox::try_new(value)?)) }
     }
-}
 
-impl<T: ?Sized> Gc<T> {
-    /// Returns `true` if the two `Gc`s point to the same allocation.
-    pub fn ptr_eq(this: &Gc<T>, other: &Gc<T>) -> bool {
-        GcBox::ptr_eq(this.inner(), other.inner())
+    /// Constructs a new `Pin<Gc<T>>`, so that a value which isn't `Unpin`,
+    /// such as a self-referential future, can be stored in a `Gc`.
+    ///
+    /// The collector never moves an allocation, and drops the value in
+    /// place before freeing its memory. A `Pin<Gc<T>>` doesn't give access
+    /// to the `Gc` itself, so [`Gc::get_mut_scanning`] and
+    /// [`Gc::make_mut_scanning`] can't be used to move the value out either.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    /// use std::pin::Pin;
+    ///
+    /// let pinned: Pin<Gc<u32>> = Gc::pin(5);
+    /// let other = pinned.clone();
+    /// assert_eq!(*pinned.as_ref(), 5);
+    /// assert_eq!(*other, 5);
+    /// ```
+    pub fn pin(value: T) -> Pin<Self> {
+        unsafe { Pin::new_unchecked(Gc::new(value)) }
     }
 
-    /// Provides a raw pointer to the data.
+    /// Constructs a new `Gc` with uninitialized contents.
+    ///
+    /// The collector doesn't trace, finalize or drop the contents until
+    /// [`Gc::assume_init`] is called. If the `Gc` is dropped before then,
+    /// the contents are leaked.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
     ///
     /// # Examples
     ///
     /// ```
     /// use gc::Gc;
     ///
-    /// let x = Gc::new("hello".to_owned());
-    /// let y = x.clone();
-    /// let x_ptr = Gc::as_ptr(&x);
-    /// assert_eq!(x_ptr, Gc::as_ptr(&y));
-    /// assert_eq!(unsafe { &*x_ptr }, "hello");
+    /// let mut five = Gc::<u32>::new_uninit();
+    /// Gc::get_mut_scanning(&mut five).unwrap().write(5);
+    /// let five = unsafe { Gc::assume_init(five) };
+    /// assert_eq!(*five, 5);
     /// ```
-    pub fn as_ptr(this: &Gc<T>) -> *const T {
-        let ptr = this.inner_ptr();
-        GcBox::value_ptr(ptr)
+    pub fn new_uninit() -> Gc<MaybeUninit<T>> {
+        unsafe { Gc::from_gcbox(GcBox::new_uninit(false)) }
     }
-}
 
-/// Returns the given pointer with its root bit cleared.
-unsafe fn clear_root_bit<T: ?Sized>(ptr: NonNull<GcBox<T>>) -> NonNull<GcBox<T>> {
-    let ptr = ptr.as_ptr();
-    let data = ptr.cast::<u8>();
-    let addr = data as isize;
-    let ptr = set_data_ptr(ptr, data.wrapping_offset((addr & !1) - addr));
-    NonNull::new_unchecked(ptr)
+    /// Constructs a new `Gc` with uninitialized contents, with the memory
+    /// filled with zero bytes.
+    ///
+    /// See [`MaybeUninit::zeroed`] for whether that's a valid `T`, and
+    /// [`Gc::new_uninit`] for how the contents are treated until then.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let zero = Gc::<u32>::new_zeroed();
+    /// let zero = unsafe { Gc::assume_init(zero) };
+    /// assert_eq!(*zero, 0);
+    /// ```
+    pub fn new_zeroed() -> Gc<MaybeUninit<T>> {
+        unsafe { Gc::from_gcbox(GcBox::new_uninit(true)) }
+    }
 }
 
-impl<T: ?Sized> Gc<T> {
-    fn rooted(&self) -> bool {
-        self.ptr_root.get().as_ptr().cast::<u8>() as usize & 1 != 0
+impl<T: Trace> Gc<MaybeUninit<T>> {
+    /// Converts to `Gc<T>`, once the contents have been initialized.
+    ///
+    /// The `Gc`s in the contents are unrooted, as they are now on the heap,
+    /// and the collector starts tracing them. The root count of the
+    /// allocation itself is unchanged.
+    ///
+    /// # Safety
+    ///
+    /// As with [`MaybeUninit::assume_init`], the contents must be
+    /// initialized. Other `Gc<MaybeUninit<T>>`s pointing to the same
+    /// allocation must not be used to write to it afterwards.
+    pub unsafe fn assume_init(this: Self) -> Gc<T> {
+        this.inner().assume_init();
+        let this = ManuallyDrop::new(this);
+        Gc {
+            ptr_root: Cell::new(this.ptr_root.get().cast()),
+            marker: PhantomData,
+        }
     }
+}
 
-    unsafe fn set_root(&self) {
-        let ptr = self.ptr_root.get().as_ptr();
-        let data = ptr.cast::<u8>();
-        let addr = data as isize;
-        let ptr = set_data_ptr(ptr, data.wrapping_offset((addr | 1) - addr));
-        self.ptr_root.set(NonNull::new_unchecked(ptr));
-    }
+impl<T: Trace> Gc<Vec<T>> {
+    /// Returns a `Gc` to an empty `Vec<T>` which is shared by every caller on
+    /// the current thread.
+    ///
+    /// The shared allocation is made on first use and stays alive until the
+    /// thread exits, so this avoids an allocation per empty vector in
+    /// structures with many empty leaves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let a: Gc<Vec<u8>> = Gc::empty_vec();
+    /// let b: Gc<Vec<u8>> = Gc::empty_vec();
+    /// assert!(a.is_empty());
+    /// assert!(Gc::ptr_eq(&a, &b));
+    /// ```
+    pub fn empty_vec() -> Self {
+        thread_local!(static EMPTY_VECS: RefCell<HashMap<TypeId, Box<dyn Any>>> =
+            RefCell::new(HashMap::new()));
+
+        let shared = EMPTY_VECS.with(|vecs| {
+            vecs.borrow()
+                .get(&TypeId::of::<T>())
+                .and_then(|v| v.downcast_ref::<Gc<Vec<T>>>())
+                .cloned()
+        });
+        if let Some(shared) = shared {
+            return shared;
+        }
 
-    unsafe fn clear_root(&self) {
-        self.ptr_root.set(clear_root_bit(self.ptr_root.get()));
+        // Allocate outside of the borrow, as this may trigger a collection
+        // which runs arbitrary finalizers.
+        let empty = Gc::new(Vec::new());
+        EMPTY_VECS.with(|vecs| {
+            vecs.borrow_mut()
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Box::new(empty.clone()));
+        });
+        empty
     }
+}
 
-    #[inline]
-    fn inner_ptr(&self) -> *mut GcBox<T> {
-        // If we are currently in the dropping phase of garbage collection,
-        // it would be undefined behavior to dereference an unrooted Gc.
-        // By opting into `Trace` you agree to not dereference this pointer
-        // within your drop method, meaning that it should be safe.
-        //
-        // This assert exists just in case.
-        assert!(finalizer_safe() || self.rooted());
-
-        unsafe { clear_root_bit(self.ptr_root.get()).as_ptr() }
-    }
+impl<T: Trace + ?Sized> Gc<T> {
+    /// Returns a mutable reference into the given `Gc`, if no other `Gc`
+    /// points to the same allocation.
+    ///
+    /// As with [`Gc::make_mut_scanning`], `Gc`s stored in other allocations
+    /// count too. Unless another rooted `Gc` points to the allocation, this
+    /// traces every allocation on the heap, so it takes time proportional to
+    /// the size of the heap. It returns `None` if the heap can't be traced
+    /// because a collection is in progress.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let mut x = Gc::new(3);
+    /// *Gc::get_mut_scanning(&mut x).unwrap() = 4;
+    /// assert_eq!(*x, 4);
+    ///
+    /// let _y = x.clone();
+    /// assert!(Gc::get_mut_scanning(&mut x).is_none());
+    /// ```
+    pub fn get_mut_scanning(this: &mut Self) -> Option<GcRefMut<'_, T>> {
+        if !this.inner().is_unique() {
+            return None;
+        }
 
-    #[inline]
-    fn inner(&self) -> &GcBox<T> {
-        unsafe { &*self.inner_ptr() }
+        unsafe {
+            let gcbox = this.inner();
+            gcbox.value().root();
+            gcbox.set_borrowed(true);
+        }
+        Some(GcRefMut { gc: this })
     }
 }
 
-impl<T: ?Sized> Gc<T> {
-    /// Consumes the `Gc`, returning the wrapped pointer.
+impl<T: Trace + Clone> Gc<T> {
+    /// Makes a mutable reference into the given `Gc`, scanning the heap to
+    /// find out whether it is shared.
     ///
-    /// To avoid a memory leak, the pointer must be converted back into a `Gc`
-    /// using [`Gc::from_raw`][from_raw].
+    /// If no other `Gc` points to the same allocation, its value is mutated
+    /// in place. Otherwise, the value is cloned into a new allocation, which
+    /// `this` is changed to point to, and the other `Gc`s are left untouched.
     ///
-    /// [from_raw]: struct.Gc.html#method.from_raw
+    /// Unlike `Rc::make_mut`, this is not a cheap check. `Gc`s stored in
+    /// other allocations are unrooted, so finding them requires tracing every
+    /// allocation on the heap. This is skipped when another rooted `Gc`
+    /// points to the allocation, but in the common case of `this` being the
+    /// only root, each call takes time proportional to the size of the heap.
+    /// Calling it in a loop is therefore quadratic; mutate through a
+    /// [`GcCell`] instead where that matters. If the heap can't be traced
+    /// because a collection is in progress, the value is cloned.
+    ///
+    /// The `Gc`s in the value are rooted until the returned `GcRefMut` is
+    /// dropped, as with `GcCell::borrow_mut`.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
     ///
     /// # Examples
     ///
     /// ```
     /// use gc::Gc;
     ///
-    /// let x = Gc::new(22);
-    /// let x_ptr = Gc::into_raw(x);
-    /// assert_eq!(unsafe { *x_ptr }, 22);
-    /// unsafe { Gc::from_raw(x_ptr) };
+    /// let mut data = Gc::new(5);
+    /// *Gc::make_mut_scanning(&mut data) += 1; // Won't clone anything
+    /// let mut other_data = data.clone(); // Won't clone inner data
+    /// *Gc::make_mut_scanning(&mut data) += 1; // Clones inner data
+    /// *Gc::make_mut_scanning(&mut data) += 1; // Won't clone anything
+    /// *Gc::make_mut_scanning(&mut other_data) *= 2; // Won't clone anything
+    ///
+    /// assert_eq!(*data, 8);
+    /// assert_eq!(*other_data, 12);
     /// ```
-    pub fn into_raw(this: Self) -> *const T {
-        GcBox::value_ptr(ManuallyDrop::new(this).inner_ptr())
+    pub fn make_mut_scanning(this: &mut Self) -> GcRefMut<'_, T> {
+        if !this.inner().is_unique() {
+            *this = Gc::new(T::clone(this));
+        }
+
+        unsafe {
+            let gcbox = this.inner();
+            gcbox.value().root();
+            gcbox.set_borrowed(true);
+        }
+        GcRefMut { gc: this }
     }
 
-    /// Constructs an `Gc` from a raw pointer.
-    ///
-    /// The raw pointer must have been previously returned by a call to a
-    /// [`Gc::into_raw`][into_raw].
+    /// Converts the given `Gc` into an `Rc` holding a clone of its value, if
+    /// no other `Gc` points to the same allocation, or returns `None`
+    /// otherwise.
     ///
-    /// This function is unsafe because improper use may lead to memory
-    /// problems. For example, a use-after-free will occur if the function is
-    /// called twice on the same raw pointer.
+    /// As with [`Gc::get_mut_scanning`], `Gc`s stored in other allocations count
+    /// too. Unless another rooted `Gc` points to the allocation, this traces
+    /// every allocation on the heap, so it takes time proportional to the size
+    /// of the heap. It returns `None` if the heap can't be traced because a
+    /// collection is in progress. The allocation is dropped along with `this`,
+    /// and freed by the next collection.
     ///
-    /// [into_raw]: struct.Gc.html#method.into_raw
+    /// This is meant for handing acyclic data to APIs which take an `Rc`.
+    /// Identity is not preserved: the `Rc` doesn't point to the `Gc`'s
+    /// allocation, so e.g. [`Gc::ptr_eq`] can't be used to relate the two.
+    /// Any `Gc`s in the value are cloned, and stay rooted for as long as the
+    /// `Rc` keeps them alive.
     ///
     /// # Examples
     ///
     /// ```
     /// use gc::Gc;
+    /// use std::rc::Rc;
     ///
-    /// let x = Gc::new(22);
-    /// let x_ptr = Gc::into_raw(x);
+    /// let x = Gc::new(String::from("x"));
+    /// let y = x.clone();
+    /// assert!(Gc::try_into_rc_scanning(x).is_none());
     ///
-    /// unsafe {
-    ///     // Convert back to an `Gc` to prevent leak.
-    ///     let x = Gc::from_raw(x_ptr);
-    ///     assert_eq!(*x, 22);
+    /// let rc: Rc<String> = Gc::try_into_rc_scanning(y).unwrap();
+    /// assert_eq!(*rc, "x");
+    /// ```
+    pub fn try_into_rc_scanning(this: Self) -> Option<Rc<T>> {
+        if this.inner().is_unique() {
+            Some(Rc::new(T::clone(&this)))
+        } else {
+            None
+        }
+    }
+
+    /// Moves the value out of the given `Rc` into a new garbage-collected
+    /// allocation, cloning it if the `Rc` is shared.
     ///
-    ///     // Further calls to `Gc::from_raw(x_ptr)` would be memory unsafe.
-    /// }
+    /// Identity is not preserved: the other clones of the `Rc` keep the
+    /// original value, and aren't affected by changes made through the
+    /// `Gc`.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    ///
+    /// # Examples
     ///
-    /// // The memory can be freed at any time after `x` went out of scope above
-    /// // (when the collector is run), which would result in `x_ptr` dangling!
     /// ```
-    pub unsafe fn from_raw(ptr: *const T) -> Self {
-        // Find the offset of T in GcBox<T>. Note that Layout::extend
-        // relies on GcBox being repr(C).
-        let (_, offset) = Layout::new::<GcBoxHeader>()
-            .extend(Layout::for_value::<T>(&*ptr))
-            .unwrap();
-
-        // Reverse the offset to find the original GcBox.
-        let fake_ptr = ptr as *mut GcBox<T>;
-        let rc_ptr = set_data_ptr(fake_ptr, (ptr as *mut u8).offset(-(offset as isize)));
-
-        let gc = Gc {
-            ptr_root: Cell::new(NonNull::new_unchecked(rc_ptr)),
-            marker: PhantomData,
-        };
-        gc.set_root();
-        gc
+    /// use gc::Gc;
+    /// use std::rc::Rc;
+    ///
+    /// let rc = Rc::new(vec![1, 2]);
+    /// let gc = Gc::from_rc(rc.clone());
+    /// assert_eq!(*gc, *rc);
+    /// ```
+    pub fn from_rc(rc: Rc<T>) -> Self {
+        Gc::new(Rc::try_unwrap(rc).unwrap_or_else(|rc| T::clone(&rc)))
     }
 }
 
-impl<T: ?Sized> Finalize for Gc<T> {}
-
-unsafe impl<T: Trace + ?Sized> Trace for Gc<T> {
-    #[inline]
-    unsafe fn trace(&self) {
-        self.inner().trace_inner();
-    }
-
-    #[inline]
-    unsafe fn root(&self) {
-        assert!(!self.rooted(), "Can't double-root a Gc<T>");
-
-        // Try to get inner before modifying our state. Inner may be
-        // inaccessible due to this method being invoked during the sweeping
-        // phase, and we don't want to modify our state before panicking.
-        self.inner().root_inner();
+/// A mutable reference to the value of a `Gc`, returned by [`Gc::make_mut_scanning`]
+/// and [`Gc::get_mut_scanning`].
+pub struct GcRefMut<'a, T: Trace + ?Sized + 'static> {
+    gc: &'a mut Gc<T>,
+}
 
-        self.set_root();
-    }
+impl<'a, T: Trace + ?Sized> Deref for GcRefMut<'a, T> {
+    type Target = T;
 
     #[inline]
-    unsafe fn unroot(&self) {
-        assert!(self.rooted(), "Can't double-unroot a Gc<T>");
-
-        // Try to get inner before modifying our state. Inner may be
-        // inaccessible due to this method being invoked during the sweeping
-        // phase, and we don't want to modify our state before panicking.
-        self.inner().unroot_inner();
-
-        self.clear_root();
+    fn deref(&self) -> &T {
+        self.gc.inner().value()
     }
+}
 
+impl<'a, T: Trace + ?Sized> DerefMut for GcRefMut<'a, T> {
     #[inline]
-    fn finalize_glue(&self) {
-        Finalize::finalize(self);
+    fn deref_mut(&mut self) -> &mut T {
+        // The `Gc` is unique and mutably borrowed, and the collector doesn't
+        // trace the value while it is borrowed.
+        unsafe { &mut *(GcBox::value_ptr(self.gc.inner_ptr()) as *mut T) }
     }
 }
 
-impl<T: ?Sized> Clone for Gc<T> {
+impl<'a, T: Trace + ?Sized> Drop for GcRefMut<'a, T> {
     #[inline]
-    fn clone(&self) -> Self {
+    fn drop(&mut self) {
+        // Unroot the value's `Gc`s now that it's back on the heap.
         unsafe {
-            self.inner().root_inner();
-            let gc = Gc {
-                ptr_root: Cell::new(self.ptr_root.get()),
-                marker: PhantomData,
-            };
-            gc.set_root();
-            gc
+            let gcbox = self.gc.inner();
+            crate::gc::write_barrier(gcbox.value());
+            gcbox.value().unroot();
+            gcbox.set_borrowed(false);
         }
     }
 }
 
-impl<T: ?Sized> Deref for Gc<T> {
-    type Target = T;
-
+impl<'a, T: Trace + Debug + ?Sized> Debug for GcRefMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: Trace + Display + ?Sized> Display for GcRefMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: Trace + ?Sized> Gc<T> {
+    /// Constructs a `Gc` that points to a new `GcBox`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `GcBox` on the thread-local
+    /// `GcBox` chain.
+    #[inline]
+    unsafe fn from_gcbox(ptr: NonNull<GcBox<T>>) -> Gc<T> {
+        assert!(mem::align_of_val::<GcBox<T>>(ptr.as_ref()) > 1);
+
+        // When we create a Gc<T>, all pointers which have been moved to the
+        // heap no longer need to be rooted, so we unroot them.
+        ptr.as_ref().value().unroot();
+        let gc = Gc {
+            ptr_root: Cell::new(ptr),
+            marker: PhantomData,
+        };
+        gc.set_root();
+        gc
+    }
+}
+
+impl<T: Trace> Gc<GcCell<T>> {
+    /// Constructs a new `Gc<GcCell<T>>` with the given value, as a shorthand
+    /// for `Gc::new(GcCell::new(value))`.
+    ///
+    /// The contents are only unrooted once, as they move onto the heap, just
+    /// as with the two-step form.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let counter = Gc::new_cell(0);
+    /// *counter.borrow_mut() += 1;
+    /// assert_eq!(*counter.borrow(), 1);
+    /// ```
+    #[inline]
+    pub fn new_cell(value: T) -> Self {
+        Gc::new(GcCell::new(value))
+    }
+}
+
+impl<T: ?Sized> Gc<T> {
+    /// Returns `true` if the two `Gc`s point to the same allocation.
+    ///
+    /// This compares by identity, unlike `==`, which compares the contents.
+    pub fn ptr_eq(this: &Gc<T>, other: &Gc<T>) -> bool {
+        GcBox::ptr_eq(this.inner(), other.inner())
+    }
+
+    /// Returns `true` if the two `Gc`s point to the same allocation. This is
+    /// the same as [`ptr_eq`](#method.ptr_eq).
+    #[inline]
+    pub fn ref_eq(this: &Gc<T>, other: &Gc<T>) -> bool {
+        Gc::ptr_eq(this, other)
+    }
+
+    /// Returns a new `Gc` to an allocation referenced by `this`, e.g. through
+    /// one of its fields.
+    ///
+    /// The returned `Gc` is a root of its own, so it stays valid after `this`
+    /// is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{Finalize, Gc, Trace};
+    ///
+    /// #[derive(Trace, Finalize)]
+    /// struct Pair {
+    ///     left: Gc<u32>,
+    ///     right: Gc<u32>,
+    /// }
+    ///
+    /// let pair = Gc::new(Pair {
+    ///     left: Gc::new(1),
+    ///     right: Gc::new(2),
+    /// });
+    /// let right = Gc::project(&pair, |p| &p.right);
+    /// drop(pair);
+    /// gc::force_collect();
+    /// assert_eq!(*right, 2);
+    /// ```
+    pub fn project<U: ?Sized>(this: &Gc<T>, f: impl FnOnce(&T) -> &Gc<U>) -> Gc<U> {
+        f(this).clone()
+    }
+
+    /// Returns an iterator over `this` and the `Gc`s following it, where
+    /// `next` returns the `Gc` following a value, e.g. through a field.
+    ///
+    /// Unlike walking the chain with [`Gc::project`] or `clone`, this borrows
+    /// each `Gc` from the value before it instead of rooting a new one, as
+    /// everything reachable from `this` stays alive for as long as it is
+    /// borrowed. Borrowing through a `GcCell` along the way isn't possible,
+    /// as its guard wouldn't outlive the step.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{Finalize, Gc, Trace};
+    ///
+    /// #[derive(Trace, Finalize)]
+    /// struct Cons {
+    ///     head: u32,
+    ///     tail: Option<Gc<Cons>>,
+    /// }
+    ///
+    /// let list = (1..=3).rev().fold(None, |tail, head| {
+    ///     Some(Gc::new(Cons { head, tail }))
+    /// });
+    /// let list = list.unwrap();
+    /// let heads: Vec<u32> = Gc::successors(&list, |c| c.tail.as_ref())
+    ///     .map(|c| c.head)
+    ///     .collect();
+    /// assert_eq!(heads, [1, 2, 3]);
+    /// ```
+    pub fn successors<F>(this: &Gc<T>, next: F) -> GcSuccessors<'_, T, F>
+    where
+        F: FnMut(&T) -> Option<&Gc<T>>,
+    {
+        GcSuccessors {
+            next: Some(this),
+            succ: next,
+        }
+    }
+
+    /// Hashes the address of the allocation, consistently with
+    /// [`ptr_eq`](#method.ptr_eq).
+    ///
+    /// Unlike `Hash::hash`, this doesn't hash the contents, so it's quick and
+    /// terminates on cyclic structures. See [`GcPtrKey`] to use `Gc`s as
+    /// keys by identity.
+    pub fn ptr_hash<H: Hasher>(this: &Gc<T>, state: &mut H) {
+        GcPointer::from(this).hash(state);
+    }
+
+    /// Returns the number of rooted `Gc`s pointing to the allocation, i.e.
+    /// those not stored inside other `Gc` allocations.
+    ///
+    /// This is meant for debugging why an allocation isn't collected. Like
+    /// dereferencing, this panics if `this` is unrooted and is called while
+    /// the collector is freeing objects, e.g. from a `Drop` impl.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{Gc, GcCell};
+    ///
+    /// let x = Gc::new(1);
+    /// let y = x.clone();
+    /// assert_eq!(Gc::root_count(&x), 2);
+    ///
+    /// let holder = Gc::new(GcCell::new(Vec::new()));
+    /// holder.borrow_mut().push(y);
+    /// assert_eq!(Gc::root_count(&x), 1);
+    /// ```
+    #[cfg(feature = "unstable-stats")]
+    #[track_caller]
+    pub fn root_count(this: &Gc<T>) -> usize {
+        this.inner().root_count()
+    }
+
+    /// Returns whether the allocation is currently marked as reachable.
+    ///
+    /// Allocations are only marked while a collection is in progress, such
+    /// as an incremental one driven by [`collect_step`], and are unmarked
+    /// again when it finishes. This panics in the same cases
+    /// as [`Gc::root_count`].
+    #[cfg(feature = "unstable-stats")]
+    #[track_caller]
+    pub fn is_marked(this: &Gc<T>) -> bool {
+        this.inner().is_marked()
+    }
+
+    /// Provides a raw pointer to the data.
+    ///
+    /// This does not consume the `Gc` or change its root count. The collector
+    /// never moves allocations, so the address is the same for every `Gc` to
+    /// the allocation, but the pointer is only valid to dereference while a
+    /// `Gc` keeping the allocation alive is held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let x = Gc::new("hello".to_owned());
+    /// let y = x.clone();
+    /// let x_ptr = Gc::as_ptr(&x);
+    /// assert_eq!(x_ptr, Gc::as_ptr(&y));
+    /// assert_eq!(unsafe { &*x_ptr }, "hello");
+    /// ```
+    pub fn as_ptr(this: &Gc<T>) -> *const T {
+        let ptr = this.inner_ptr();
+        GcBox::value_ptr(ptr)
+    }
+
+    /// Returns the number of bytes the allocation takes on the heap,
+    /// including the collector's header and any padding.
+    ///
+    /// This is the amount the allocation adds to the bytes allocated, which
+    /// decide when to collect, so it can be used to attribute heap usage to
+    /// particular objects. It doesn't include memory the value owns outside
+    /// of the allocation, such as the buffer of a `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let small = Gc::new([0u64; 10]);
+    /// let large = Gc::new([0u64; 20]);
+    /// assert!(Gc::allocated_size(&small) > 80);
+    /// assert_eq!(Gc::allocated_size(&large) - Gc::allocated_size(&small), 80);
+    /// ```
+    pub fn allocated_size(this: &Gc<T>) -> usize {
+        mem::size_of_val::<GcBox<T>>(this.inner())
+    }
+
+    /// Returns a pinned reference to the value of a `Gc` which wasn't
+    /// created with [`Gc::pin`].
+    ///
+    /// # Safety
+    ///
+    /// From now on, the value must never be moved, until it is dropped by
+    /// the collector. In particular, neither [`Gc::get_mut_scanning`] nor
+    /// [`Gc::make_mut_scanning`] may be used to mutably borrow it through any `Gc` to
+    /// the same allocation, unless `T` is `Unpin`.
+    pub unsafe fn as_pin_ref(this: &Gc<T>) -> Pin<&T> {
+        Pin::new_unchecked(&**this)
+    }
+
+    /// Returns a reference to the data, or `None` if it can't safely be
+    /// dereferenced right now.
+    ///
+    /// Dereferencing a `Gc` which is not a root panics while the collector
+    /// is sweeping, as the data it points to may already have been freed.
+    /// This lets code that can run during the sweep, such as `Drop`
+    /// implementations, probe other objects without risking that panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let x = Gc::new(5);
+    /// assert_eq!(Gc::try_deref(&x), Some(&5));
+    /// ```
+    pub fn try_deref(this: &Gc<T>) -> Option<&T> {
+        if finalizer_safe() || this.rooted() {
+            Some(this.inner().value())
+        } else {
+            None
+        }
+    }
+}
+
+/// An iterator over a chain of `Gc`s which doesn't root them, returned by
+/// [`Gc::successors`].
+pub struct GcSuccessors<'a, T: ?Sized + 'static, F> {
+    next: Option<&'a Gc<T>>,
+    succ: F,
+}
+
+impl<'a, T: Trace + ?Sized, F> Iterator for GcSuccessors<'a, T, F>
+where
+    F: FnMut(&'a T) -> Option<&'a Gc<T>>,
+{
+    type Item = &'a Gc<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a Gc<T>> {
+        let item = self.next.take()?;
+        self.next = (self.succ)(item);
+        Some(item)
+    }
+}
+
+/// Returns the given pointer with its root bit cleared.
+unsafe fn clear_root_bit<T: ?Sized>(ptr: NonNull<GcBox<T>>) -> NonNull<GcBox<T>> {
+    let ptr = ptr.as_ptr();
+    let data = ptr.cast::<u8>();
+    let addr = data as isize;
+    let ptr = set_data_ptr(ptr, data.wrapping_offset((addr & !1) - addr));
+    NonNull::new_unchecked(ptr)
+}
+
+impl<T: ?Sized> Gc<T> {
+    fn rooted(&self) -> bool {
+        self.ptr_root.get().as_ptr().cast::<u8>() as usize & 1 != 0
+    }
+
+    unsafe fn set_root(&self) {
+        let ptr = self.ptr_root.get().as_ptr();
+        let data = ptr.cast::<u8>();
+        let addr = data as isize;
+        let ptr = set_data_ptr(ptr, data.wrapping_offset((addr | 1) - addr));
+        self.ptr_root.set(NonNull::new_unchecked(ptr));
+    }
+
+    unsafe fn clear_root(&self) {
+        self.ptr_root.set(clear_root_bit(self.ptr_root.get()));
+    }
+
+    #[inline]
+    #[track_caller]
+    fn inner_ptr(&self) -> *mut GcBox<T> {
+        // If we are currently in the dropping phase of garbage collection,
+        // it would be undefined behavior to dereference an unrooted Gc.
+        // By opting into `Trace` you agree to not dereference this pointer
+        // within your drop method, meaning that it should be safe.
+        //
+        // This check exists just in case.
+        if !(finalizer_safe() || self.rooted()) {
+            panic!(
+                "dereferenced a Gc<{}> while the garbage collector was freeing objects; \
+                 Drop impls of garbage-collected types must not dereference Gcs, \
+                 use Finalize instead",
+                std::any::type_name::<T>()
+            );
+        }
+
+        unsafe { clear_root_bit(self.ptr_root.get()).as_ptr() }
+    }
+
+    #[inline]
+    #[track_caller]
+    fn inner(&self) -> &GcBox<T> {
+        unsafe { &*self.inner_ptr() }
+    }
+}
+
+impl<T: ?Sized> Gc<T> {
+    /// Consumes the `Gc`, returning the wrapped pointer.
+    ///
+    /// To avoid a memory leak, the pointer must be converted back into a `Gc`
+    /// using [`Gc::from_raw`][from_raw].
+    ///
+    /// [from_raw]: struct.Gc.html#method.from_raw
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let x = Gc::new(22);
+    /// let x_ptr = Gc::into_raw(x);
+    /// assert_eq!(unsafe { *x_ptr }, 22);
+    /// unsafe { Gc::from_raw(x_ptr) };
+    /// ```
+    pub fn into_raw(this: Self) -> *const T {
+        GcBox::value_ptr(ManuallyDrop::new(this).inner_ptr())
+    }
+
+    /// Constructs an `Gc` from a raw pointer.
+    ///
+    /// The raw pointer must have been previously returned by a call to a
+    /// [`Gc::into_raw`][into_raw].
+    ///
+    /// This function is unsafe because improper use may lead to memory
+    /// problems. For example, a use-after-free will occur if the function is
+    /// called twice on the same raw pointer.
+    ///
+    /// [into_raw]: struct.Gc.html#method.into_raw
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let x = Gc::new(22);
+    /// let x_ptr = Gc::into_raw(x);
+    ///
+    /// unsafe {
+    ///     // Convert back to an `Gc` to prevent leak.
+    ///     let x = Gc::from_raw(x_ptr);
+    ///     assert_eq!(*x, 22);
+    ///
+    ///     // Further calls to `Gc::from_raw(x_ptr)` would be memory unsafe.
+    /// }
+    ///
+    /// // The memory can be freed at any time after `x` went out of scope above
+    /// // (when the collector is run), which would result in `x_ptr` dangling!
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Gc::into_raw`][into_raw], and must
+    /// only be converted back into a `Gc` once.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        // Find the offset of T in GcBox<T>. Note that Layout::extend
+        // relies on GcBox being repr(C).
+        let (_, offset) = Layout::new::<GcBoxHeader>()
+            .extend(Layout::for_value::<T>(&*ptr))
+            .unwrap();
+
+        // Reverse the offset to find the original GcBox.
+        let fake_ptr = ptr as *mut GcBox<T>;
+        let rc_ptr = set_data_ptr(fake_ptr, (ptr as *mut u8).offset(-(offset as isize)));
+
+        let gc = Gc {
+            ptr_root: Cell::new(NonNull::new_unchecked(rc_ptr)),
+            marker: PhantomData,
+        };
+        gc.set_root();
+        gc
+    }
+}
+
+impl<T: ?Sized> Finalize for Gc<T> {}
+
+unsafe impl<T: Trace + ?Sized> Trace for Gc<T> {
+    #[inline]
+    unsafe fn trace(&self) {
+        if crate::gc::trace_redirected()
+            && (crate::gc::record_edge(GcPointer::from(self))
+                || crate::gc::defer_trace(GcPointer::from(self)))
+        {
+            return;
+        }
+        self.inner().trace_inner();
+    }
+
+    #[inline]
+    unsafe fn root(&self) {
+        assert!(!self.rooted(), "Can't double-root a Gc<T>");
+
+        // Try to get inner before modifying our state. Inner may be
+        // inaccessible due to this method being invoked during the sweeping
+        // phase, and we don't want to modify our state before panicking.
+        self.inner().root_inner();
+
+        self.set_root();
+    }
+
+    #[inline]
+    unsafe fn unroot(&self) {
+        assert!(self.rooted(), "Can't double-unroot a Gc<T>");
+
+        // Try to get inner before modifying our state. Inner may be
+        // inaccessible due to this method being invoked during the sweeping
+        // phase, and we don't want to modify our state before panicking.
+        self.inner().unroot_inner();
+
+        self.clear_root();
+    }
+
+    #[inline]
+    fn finalize_glue(&self) {
+        Finalize::finalize(self);
+    }
+
+    // The pointee is finalized when it is collected itself.
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        visitor(GcPointer::from(self));
+    }
+}
+
+impl<T: ?Sized> Clone for Gc<T> {
+    /// Returns another rooted `Gc` pointing to the same allocation.
+    ///
+    /// # Panics
+    ///
+    /// The root count of an allocation is stored in the low bits of a
+    /// `usize` in its header, next to six bits of flags for the collector.
+    /// This panics if the allocation already has 2^58 - 1 rooted `Gc`s
+    /// pointing to it on 64-bit targets, or 2^26 - 1 on 32-bit ones. `Gc`s
+    /// stored in other allocations aren't rooted, and don't count towards
+    /// the limit.
+    ///
+    /// With the `compact-header` feature, the root count and flags are
+    /// packed into a `u32` on every target, to make each allocation 4 bytes
+    /// smaller on 64-bit targets. The limit is then 2^26 - 1 (67,108,863),
+    /// which can be reached, e.g. by keeping that many clones in a `Vec`.
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe {
+            self.inner().root_inner();
+            let gc = Gc {
+                ptr_root: Cell::new(self.ptr_root.get()),
+                marker: PhantomData,
+            };
+            gc.set_root();
+            gc
+        }
+    }
+
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        // Assigning a clone would root the allocation only to unroot it
+        // again, if `self` is already a root pointing to it.
+        if !(self.rooted() && GcBox::ptr_eq(self.inner(), source.inner())) {
+            *self = source.clone();
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for Gc<T> {
+    type Target = T;
+
     #[inline]
+    #[track_caller]
     fn deref(&self) -> &T {
         self.inner().value()
     }
@@ -328,10 +1004,106 @@ impl<T: Trace + Default> Default for Gc<T> {
     }
 }
 
+impl<T: Trace> Default for Gc<[T]> {
+    /// Returns a `Gc` to an empty slice.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    #[inline]
+    fn default() -> Self {
+        // A `GcBox<[T; 0]>` is laid out as a `GcBox<[T]>` of length zero,
+        // and is what the collector sees in the chain, as with
+        // `Gc::new_dst_with`.
+        let empty = Gc::into_raw(Gc::<[T; 0]>::new([]));
+        unsafe { Gc::from_raw(ptr::slice_from_raw_parts(empty.cast::<T>(), 0)) }
+    }
+}
+
+impl Default for Gc<str> {
+    /// Returns a `Gc` to an empty string.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    #[inline]
+    fn default() -> Self {
+        let empty = Gc::into_raw(Gc::<[u8]>::default());
+        // An empty slice is valid UTF-8.
+        unsafe { Gc::from_raw(empty as *const str) }
+    }
+}
+
+// How many comparisons by `Gc::value_eq` are nested on this thread. Cycles
+// are only looked for once this reaches `EQ_TRACKING_DEPTH`, so that
+// comparing shallow, acyclic values doesn't have to hash anything.
+thread_local!(static EQ_DEPTH: Cell<usize> = const { Cell::new(0) });
+const EQ_TRACKING_DEPTH: usize = 64;
+
+// The pairs of allocations which are being compared by `Gc::value_eq` past
+// `EQ_TRACKING_DEPTH`, to detect cycles.
+thread_local!(static EQ_VISITING: RefCell<HashSet<(GcPointer, GcPointer)>> = RefCell::new(HashSet::new()));
+
+impl<T: ?Sized + PartialEq> Gc<T> {
+    /// Returns `true` if the two `Gc`s point to equal values. This is what
+    /// `==` does on `Gc`s.
+    ///
+    /// This terminates on cyclic structures: comparing two allocations which
+    /// are already being compared further up returns `true`, so two cycles
+    /// are equal if comparing their values never finds a difference. Pairs
+    /// of allocations are only remembered once comparisons are nested a few
+    /// dozen levels deep, so comparing shallow values costs little more than
+    /// comparing the contents directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{Finalize, Gc, GcCell, Trace};
+    ///
+    /// #[derive(Trace, Finalize, PartialEq)]
+    /// struct Node(u32, GcCell<Option<Gc<Node>>>);
+    ///
+    /// let a = Gc::new(Node(1, GcCell::new(None)));
+    /// let b = Gc::new(Node(1, GcCell::new(None)));
+    /// *a.1.borrow_mut() = Some(a.clone());
+    /// *b.1.borrow_mut() = Some(b.clone());
+    /// assert!(Gc::value_eq(&a, &b));
+    /// assert!(!Gc::ptr_eq(&a, &b));
+    /// ```
+    pub fn value_eq(this: &Gc<T>, other: &Gc<T>) -> bool {
+        struct Nested;
+        impl Drop for Nested {
+            fn drop(&mut self) {
+                let _ = EQ_DEPTH.try_with(|depth| depth.set(depth.get() - 1));
+            }
+        }
+
+        struct Visiting((GcPointer, GcPointer));
+        impl Drop for Visiting {
+            fn drop(&mut self) {
+                let _ = EQ_VISITING.try_with(|v| v.borrow_mut().remove(&self.0));
+            }
+        }
+
+        let depth = EQ_DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        let _nested = Nested;
+        if depth < EQ_TRACKING_DEPTH {
+            return **this == **other;
+        }
+
+        let pair = (GcPointer::from(this), GcPointer::from(other));
+        if !EQ_VISITING.with(|v| v.borrow_mut().insert(pair)) {
+            return true;
+        }
+        let _visiting = Visiting(pair);
+        **this == **other
+    }
+}
+
 impl<T: ?Sized + PartialEq> PartialEq for Gc<T> {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
-        **self == **other
+        Gc::value_eq(self, other)
     }
 }
 
@@ -383,46 +1155,413 @@ impl<T: ?Sized + Display> Display for Gc<T> {
     }
 }
 
-impl<T: ?Sized + Debug> Debug for Gc<T> {
+// The allocations whose `Debug` impl is running, to detect cycles.
+thread_local!(static DEBUG_VISITING: RefCell<HashSet<GcPointer>> = RefCell::new(HashSet::new()));
+
+impl<T: ?Sized + Debug> Debug for Gc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Visiting(GcPointer);
+        impl Drop for Visiting {
+            fn drop(&mut self) {
+                let _ = DEBUG_VISITING.try_with(|v| v.borrow_mut().remove(&self.0));
+            }
+        }
+
+        let ptr = GcPointer::from(self);
+        if !DEBUG_VISITING.with(|v| v.borrow_mut().insert(ptr)) {
+            return f.write_str("...");
+        }
+        let _visiting = Visiting(ptr);
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for Gc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.inner(), f)
+    }
+}
+
+impl<T: Trace> From<T> for Gc<T> {
+    fn from(t: T) -> Self {
+        Self::new(t)
+    }
+}
+
+impl<
+        #[cfg(not(feature = "nightly"))] T: Trace,
+        #[cfg(feature = "nightly")] T: Trace + Unsize<dyn Trace> + ?Sized,
+    > From<Box<T>> for Gc<T>
+{
+    /// Moves a boxed value into a new garbage-collected
+    /// allocation. If the `nightly` crate feature is enabled, the
+    /// value may be an unsized trait object.
+    fn from(v: Box<T>) -> Gc<T> {
+        unsafe { Gc::from_gcbox(GcBox::from_box(v)) }
+    }
+}
+
+impl<T: Trace> FromIterator<T> for Gc<Vec<T>> {
+    /// Collects the items into a `Vec` in a new garbage-collected allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::Gc;
+    ///
+    /// let squares: Gc<Vec<u32>> = (1..4).map(|i| i * i).collect();
+    /// assert_eq!(*squares, [1, 4, 9]);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Gc::new(iter.into_iter().collect())
+    }
+}
+
+impl<T: ?Sized> From<&Gc<T>> for GcPointer {
+    fn from(gc: &Gc<T>) -> Self {
+        unsafe { GcPointer::new(clear_root_bit(gc.ptr_root.get())) }
+    }
+}
+
+impl<T: ?Sized + Index<I>, I> Index<I> for Gc<T> {
+    type Output = T::Output;
+
+    /// Indexes into the contents, e.g. `gc_vec[0]` for a `Gc<Vec<T>>`.
+    ///
+    /// `Gc` is immutable, so it doesn't implement `IndexMut`: to index into a
+    /// mutable collection, use a `GcCell` and
+    /// [`GcCell::borrow_index`](struct.GcCell.html#method.borrow_index).
+    #[inline]
+    fn index(&self, index: I) -> &T::Output {
+        &(**self)[index]
+    }
+}
+
+impl<T: ?Sized> std::borrow::Borrow<T> for Gc<T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> std::convert::AsRef<T> for Gc<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+/// A `Gc` which is hashed and compared by the address of its allocation,
+/// rather than by its contents.
+///
+/// This allows using `Gc`s as keys in a `HashMap` or `HashSet` by identity,
+/// even if their contents don't implement `Hash` or `Eq`, or are cyclic.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, GcPtrKey};
+/// use std::collections::HashSet;
+///
+/// let a = Gc::new(1);
+/// let mut seen = HashSet::new();
+/// assert!(seen.insert(GcPtrKey(a.clone())));
+/// assert!(!seen.insert(GcPtrKey(a)));
+/// assert!(seen.insert(GcPtrKey(Gc::new(1))));
+/// ```
+pub struct GcPtrKey<T: ?Sized + 'static>(pub Gc<T>);
+
+impl<T: ?Sized> Hash for GcPtrKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Gc::ptr_hash(&self.0, state);
+    }
+}
+
+impl<T: ?Sized> PartialEq for GcPtrKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Gc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T: ?Sized> Eq for GcPtrKey<T> {}
+
+impl<T: ?Sized> Clone for GcPtrKey<T> {
+    fn clone(&self) -> Self {
+        GcPtrKey(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Debug for GcPtrKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GcPtrKey")
+            .field(&GcPointer::from(&self.0))
+            .finish()
+    }
+}
+
+impl<T: ?Sized> From<Gc<T>> for GcPtrKey<T> {
+    fn from(gc: Gc<T>) -> Self {
+        GcPtrKey(gc)
+    }
+}
+
+impl<T: ?Sized> Finalize for GcPtrKey<T> {}
+
+unsafe impl<T: Trace + ?Sized> Trace for GcPtrKey<T> {
+    custom_trace!(this, {
+        mark(&this.0);
+    });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        self.0.visit_children(visitor);
+    }
+}
+
+/// A function together with the environment it captures, which is traced.
+///
+/// The `Gc`s captured by a closure can't be traced, so a `Gc<dyn Fn()>`
+/// would keep them alive forever, or let them be freed too early. A
+/// `TracedFn` instead keeps the captured state in `env`, and calls a plain
+/// function pointer with it.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, GcCell, TracedFn};
+///
+/// let total = Gc::new(GcCell::new(0));
+/// let add = Gc::new(TracedFn::new(total.clone(), |total, n: u32| {
+///     *total.borrow_mut() += n;
+/// }));
+/// add.call(2);
+/// add.call(3);
+/// assert_eq!(*total.borrow(), 5);
+/// ```
+pub struct TracedFn<Env: 'static, Args: 'static, Ret: 'static> {
+    env: Env,
+    f: fn(&Env, Args) -> Ret,
+}
+
+impl<Env, Args, Ret> TracedFn<Env, Args, Ret> {
+    /// Creates a `TracedFn` calling `f` with `env`.
+    ///
+    /// Closures which don't capture anything can be passed as `f`.
+    #[inline]
+    pub fn new(env: Env, f: fn(&Env, Args) -> Ret) -> Self {
+        TracedFn { env, f }
+    }
+
+    /// Calls the function with the environment and `args`.
+    ///
+    /// Several arguments can be passed as a tuple.
+    #[inline]
+    pub fn call(&self, args: Args) -> Ret {
+        (self.f)(&self.env, args)
+    }
+
+    /// Returns the environment passed to the function.
+    #[inline]
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+}
+
+impl<Env: Clone, Args, Ret> Clone for TracedFn<Env, Args, Ret> {
+    fn clone(&self) -> Self {
+        TracedFn {
+            env: self.env.clone(),
+            f: self.f,
+        }
+    }
+}
+
+impl<Env, Args, Ret> Finalize for TracedFn<Env, Args, Ret> {}
+
+unsafe impl<Env: Trace, Args, Ret> Trace for TracedFn<Env, Args, Ret> {
+    custom_trace!(this, {
+        mark(&this.env);
+    });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        self.env.needs_finalize()
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        self.env.visit_children(visitor);
+    }
+}
+
+/// A clone-on-write smart pointer like `Cow`, whose owned variant is a
+/// `Gc<T>`.
+///
+/// Cloning a `GcCow` is cheap in both variants, and making it owned only
+/// clones the value once, as the resulting `Gc` is shared.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, GcCow};
+///
+/// let text = String::from("shared");
+/// let borrowed = GcCow::Borrowed(&text);
+/// let owned: Gc<String> = borrowed.clone().into_owned();
+/// assert_eq!(*owned, "shared");
+///
+/// let again = GcCow::Owned(owned.clone()).into_owned();
+/// assert!(Gc::ptr_eq(&owned, &again));
+/// assert_eq!(borrowed, GcCow::Owned(again));
+/// ```
+pub enum GcCow<'a, T: ?Sized + 'static> {
+    /// Borrowed data.
+    Borrowed(&'a T),
+    /// Garbage-collected owned data.
+    Owned(Gc<T>),
+}
+
+impl<'a, T: ?Sized> GcCow<'a, T> {
+    /// Returns `true` if the data is borrowed.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, GcCow::Borrowed(_))
+    }
+
+    /// Returns `true` if the data is owned.
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+}
+
+impl<'a, T: Trace + Clone> GcCow<'a, T> {
+    /// Extracts the owned `Gc`, cloning the data into a new one if it's
+    /// borrowed.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    pub fn into_owned(self) -> Gc<T> {
+        match self {
+            GcCow::Borrowed(value) => Gc::new(value.clone()),
+            GcCow::Owned(gc) => gc,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Deref for GcCow<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        match self {
+            GcCow::Borrowed(value) => value,
+            GcCow::Owned(gc) => gc,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Clone for GcCow<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            GcCow::Borrowed(value) => GcCow::Borrowed(value),
+            GcCow::Owned(gc) => GcCow::Owned(gc.clone()),
+        }
+    }
+}
+
+impl<'a, T: ?Sized> From<&'a T> for GcCow<'a, T> {
+    #[inline]
+    fn from(value: &'a T) -> Self {
+        GcCow::Borrowed(value)
+    }
+}
+
+impl<'a, T: ?Sized> From<Gc<T>> for GcCow<'a, T> {
+    #[inline]
+    fn from(gc: Gc<T>) -> Self {
+        GcCow::Owned(gc)
+    }
+}
+
+impl<'a, T: ?Sized> std::borrow::Borrow<T> for GcCow<'a, T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> std::convert::AsRef<T> for GcCow<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, 'b, T: ?Sized + PartialEq> PartialEq<GcCow<'b, T>> for GcCow<'a, T> {
+    #[inline]
+    fn eq(&self, other: &GcCow<'b, T>) -> bool {
+        **self == **other
+    }
+}
+
+impl<'a, T: ?Sized + Eq> Eq for GcCow<'a, T> {}
+
+impl<'a, T: ?Sized + PartialOrd> PartialOrd for GcCow<'a, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<'a, T: ?Sized + Ord> Ord for GcCow<'a, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<'a, T: ?Sized + Hash> Hash for GcCow<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<'a, T: ?Sized + Debug> Debug for GcCow<'a, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         Debug::fmt(&**self, f)
     }
 }
 
-impl<T: ?Sized> fmt::Pointer for Gc<T> {
+impl<'a, T: ?Sized + Display> Display for GcCow<'a, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Pointer::fmt(&self.inner(), f)
+        Display::fmt(&**self, f)
     }
 }
 
-impl<T: Trace> From<T> for Gc<T> {
-    fn from(t: T) -> Self {
-        Self::new(t)
-    }
-}
+impl<'a, T: ?Sized> Finalize for GcCow<'a, T> {}
 
-impl<
-        #[cfg(not(feature = "nightly"))] T: Trace,
-        #[cfg(feature = "nightly")] T: Trace + Unsize<dyn Trace> + ?Sized,
-    > From<Box<T>> for Gc<T>
-{
-    /// Moves a boxed value into a new garbage-collected
-    /// allocation. If the `nightly` crate feature is enabled, the
-    /// value may be an unsized trait object.
-    fn from(v: Box<T>) -> Gc<T> {
-        unsafe { Gc::from_gcbox(GcBox::from_box(v)) }
-    }
-}
+// As with `&T`, the `Gc`s behind a borrow are traced by their owner.
+unsafe impl<'a, T: Trace + ?Sized> Trace for GcCow<'a, T> {
+    custom_trace!(this, {
+        if let GcCow::Owned(gc) = this {
+            mark(gc);
+        }
+    });
 
-impl<T: ?Sized> std::borrow::Borrow<T> for Gc<T> {
-    fn borrow(&self) -> &T {
-        self
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        false
     }
-}
 
-impl<T: ?Sized> std::convert::AsRef<T> for Gc<T> {
-    fn as_ref(&self) -> &T {
-        self
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        if let GcCow::Owned(gc) = self {
+            gc.visit_children(visitor);
+        }
     }
 }
 
@@ -430,12 +1569,14 @@ impl<T: ?Sized> std::convert::AsRef<T> for Gc<T> {
 // GcCell //
 ////////////
 
-/// The `BorrowFlag` used by GC is split into 2 parts. the upper 63 or 31 bits
+/// The `BorrowFlag` used by GC is split into 3 parts. the upper 62 or 30 bits
 /// (depending on the architecture) are used to store the number of borrowed
 /// references to the type. The low bit is used to record the rootedness of the
-/// type.
+/// type, or, while it's mutably borrowed, of its contents, and the next one
+/// whether it was mutated through
+/// `GcCell::borrow_mut_tracked` since the last `GcCell::take_dirty`.
 ///
-/// This means that `GcCell` can have, at maximum, half as many outstanding
+/// This means that `GcCell` can have, at maximum, a quarter as many outstanding
 /// borrows as `RefCell` before panicking. I don't think that will be a problem.
 #[derive(Copy, Clone)]
 struct BorrowFlag(usize);
@@ -448,7 +1589,9 @@ enum BorrowState {
 }
 
 const ROOT: usize = 1;
-const WRITING: usize = !1;
+const DIRTY: usize = 0b10;
+const FLAGS: usize = ROOT | DIRTY;
+const WRITING: usize = !FLAGS;
 const UNUSED: usize = 0;
 
 /// The base borrowflag init is rooted, and has no outstanding borrows.
@@ -456,7 +1599,7 @@ const BORROWFLAG_INIT: BorrowFlag = BorrowFlag(1);
 
 impl BorrowFlag {
     fn borrowed(self) -> BorrowState {
-        match self.0 & !ROOT {
+        match self.0 & !FLAGS {
             UNUSED => BorrowState::Unused,
             WRITING => BorrowState::Writing,
             _ => BorrowState::Reading,
@@ -468,46 +1611,74 @@ impl BorrowFlag {
     }
 
     fn set_writing(self) -> Self {
-        // Set every bit other than the root bit, which is preserved
+        // Set every bit other than the root and dirty bits, which are preserved
         BorrowFlag(self.0 | WRITING)
     }
 
     fn set_unused(self) -> Self {
-        // Clear every bit other than the root bit, which is preserved
-        BorrowFlag(self.0 & ROOT)
+        // Clear every bit other than the root and dirty bits, which are preserved
+        BorrowFlag(self.0 & FLAGS)
     }
 
     fn add_reading(self) -> Self {
         assert!(self.borrowed() != BorrowState::Writing);
-        // Add 1 to the integer starting at the second binary digit. As our
+        // Add 1 to the integer starting at the third binary digit. As our
         // borrowstate is not writing, we know that overflow cannot happen, so
         // this is equivalent to the following, more complicated, expression:
         //
-        // BorrowFlag((self.0 & ROOT) | (((self.0 >> 1) + 1) << 1))
-        BorrowFlag(self.0 + 0b10)
+        // BorrowFlag((self.0 & FLAGS) | (((self.0 >> 2) + 1) << 2))
+        BorrowFlag(self.0 + 0b100)
     }
 
     fn sub_reading(self) -> Self {
         assert!(self.borrowed() == BorrowState::Reading);
-        // Subtract 1 from the integer starting at the second binary digit. As
+        // Subtract 1 from the integer starting at the third binary digit. As
         // our borrowstate is not writing or unused, we know that overflow or
         // undeflow cannot happen, so this is equivalent to the following, more
         // complicated, expression:
         //
-        // BorrowFlag((self.0 & ROOT) | (((self.0 >> 1) - 1) << 1))
-        BorrowFlag(self.0 - 0b10)
+        // BorrowFlag((self.0 & FLAGS) | (((self.0 >> 2) - 1) << 2))
+        BorrowFlag(self.0 - 0b100)
     }
 
     fn set_rooted(self, rooted: bool) -> Self {
         // Preserve the non-root bits
         BorrowFlag((self.0 & !ROOT) | usize::from(rooted))
     }
+
+    fn dirty(self) -> bool {
+        self.0 & DIRTY != 0
+    }
+
+    fn set_dirty(self, dirty: bool) -> Self {
+        // Preserve the non-dirty bits
+        BorrowFlag((self.0 & !DIRTY) | if dirty { DIRTY } else { 0 })
+    }
 }
 
 /// A mutable memory location with dynamically checked borrow rules
 /// that can be used inside of a garbage-collected pointer.
 ///
 /// This object is a `RefCell` that can be used inside of a `Gc<T>`.
+///
+/// Like `RefCell`, a `GcCell` is `Send` when its contents are, but never
+/// `Sync`, as its borrow flags aren't atomic. A `GcCell` containing a `Gc`
+/// is not `Send` either, as `Gc` is neither.
+///
+/// With the `debug-borrows` feature, a borrow which conflicts with another
+/// one panics with the address of the `GcCell`, and with where it was
+/// mutably borrowed, to find which call up the stack is holding it.
+///
+/// # Collections during a mutable borrow
+///
+/// A collection can't trace through a mutably borrowed `GcCell`, as its
+/// contents may be in the middle of being changed. Instead, the whole
+/// contents are rooted for as long as the borrow lasts, so anything they
+/// reference is kept alive, and they're unrooted again once it ends. This
+/// holds even if the borrow was narrowed to a single field with
+/// [`GcCellRefMut::map`]: the other fields stay rooted too. In debug builds,
+/// tracing a mutably borrowed `GcCell` asserts that its contents were
+/// rooted.
 pub struct GcCell<T: ?Sized + 'static> {
     flags: Cell<BorrowFlag>,
     cell: UnsafeCell<T>,
@@ -523,14 +1694,132 @@ impl<T> GcCell<T> {
         }
     }
 
+    /// Constructs a new `GcCell` on the garbage-collected heap. This is the
+    /// same as [`Gc::new_cell`].
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    #[inline]
+    pub fn boxed(value: T) -> Gc<Self>
+    where
+        T: Trace,
+    {
+        Gc::new_cell(value)
+    }
+
     /// Consumes the `GcCell`, returning the wrapped value.
     #[inline]
     pub fn into_inner(self) -> T {
         self.cell.into_inner()
     }
+
+    /// Returns a clone of the wrapped value, only borrowing it while cloning.
+    ///
+    /// The clone is a snapshot: unlike a `GcCellRef`, it can be kept while
+    /// the `GcCell` is mutably borrowed, but doesn't reflect later changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let c = GcCell::new(vec![1]);
+    /// let snapshot = c.borrow_cloned();
+    /// c.borrow_mut().push(2);
+    /// assert_eq!(snapshot, [1]);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn borrow_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.borrow().clone()
+    }
+
+    /// Returns a copy of the wrapped value.
+    ///
+    /// This is a faster `*cell.borrow()`, as it doesn't need to create and
+    /// drop a `GcCellRef`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let c = GcCell::new(5);
+    /// *c.borrow_mut() += 1;
+    /// assert_eq!(c.get(), 6);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn get(&self) -> T
+    where
+        T: Copy,
+    {
+        if self.flags.get().borrowed() == BorrowState::Writing {
+            conflicting_borrow(self, BorrowError);
+        }
+        // The value isn't mutably borrowed, and no mutable borrow can be
+        // taken while it is copied.
+        unsafe { *self.cell.get() }
+    }
 }
 
 impl<T: ?Sized> GcCell<T> {
+    /// Consumes a boxed `GcCell`, returning the wrapped value in a new `Box`.
+    ///
+    /// Unlike [`into_inner`](#method.into_inner), this also works for unsized
+    /// values such as slices and trait objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let c: Box<GcCell<[u32]>> = Box::new(GcCell::new([1, 2, 3]));
+    /// let v: Box<[u32]> = GcCell::into_boxed_inner(c);
+    /// assert_eq!(*v, [1, 2, 3]);
+    /// ```
+    pub fn into_boxed_inner(this: Box<Self>) -> Box<T> {
+        let cell_layout = Layout::for_value::<Self>(&*this);
+        let value_layout = Layout::for_value::<T>(unsafe { &*this.cell.get() });
+
+        unsafe {
+            // Since the value can't be moved out of the Box directly, and the
+            // Box needs to be deallocated without running the destructor,
+            // convert it to a raw pointer first.
+            let cell = Box::into_raw(this);
+            let value = UnsafeCell::raw_get(ptr::addr_of!((*cell).cell));
+
+            // Box only allocates for size != 0.
+            let addr = if value_layout.size() == 0 {
+                value_layout.align() as *mut u8
+            } else {
+                let addr = std::alloc::alloc(value_layout);
+                if addr.is_null() {
+                    std::alloc::handle_alloc_error(value_layout);
+                }
+                addr
+            };
+
+            // Move the data into the new allocation, which gets the metadata
+            // of the value.
+            addr.copy_from_nonoverlapping(value.cast::<u8>(), value_layout.size());
+            std::alloc::dealloc(cell.cast::<u8>(), cell_layout);
+            Box::from_raw(set_data_ptr(value, addr))
+        }
+    }
+
     /// Immutably borrows the wrapped value.
     ///
     /// The borrow lasts until the returned `GcCellRef` exits scope.
@@ -544,11 +1833,41 @@ impl<T: ?Sized> GcCell<T> {
     pub fn borrow(&self) -> GcCellRef<'_, T> {
         match self.try_borrow() {
             Ok(value) => value,
-            Err(e) => panic!("{}", e),
+            Err(e) => conflicting_borrow(self, e),
         }
     }
 }
 
+impl<T: ?Sized> GcCell<T> {
+    /// Immutably borrows an element of the wrapped collection.
+    ///
+    /// This is a shorthand for `GcCellRef::map(cell.borrow(), |c| &c[index])`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently mutably borrowed, or if `index` is
+    /// out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{Gc, GcCell};
+    ///
+    /// let c = Gc::new(GcCell::new(vec![1, 2, 3]));
+    /// assert_eq!(*c.borrow_index(1), 2);
+    /// c.borrow_mut().push(4);
+    /// assert_eq!(*c.borrow_index(3), 4);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn borrow_index<I>(&self, index: I) -> GcCellRef<'_, T::Output>
+    where
+        T: Index<I>,
+    {
+        GcCellRef::map(self.borrow(), |c| &c[index])
+    }
+}
+
 impl<T: Trace + ?Sized> GcCell<T> {
     /// Mutably borrows the wrapped value.
     ///
@@ -563,9 +1882,50 @@ impl<T: Trace + ?Sized> GcCell<T> {
     pub fn borrow_mut(&self) -> GcCellRefMut<'_, T> {
         match self.try_borrow_mut() {
             Ok(value) => value,
-            Err(e) => panic!("{}", e),
+            Err(e) => conflicting_borrow(self, e),
         }
     }
+
+    /// Mutably borrows the wrapped value, and marks the `GcCell` as dirty
+    /// once the borrow ends.
+    ///
+    /// This is meant for invalidating caches derived from the contents: see
+    /// [`take_dirty`](#method.take_dirty).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed.
+    pub fn borrow_mut_tracked(&self) -> GcCellRefMut<'_, T> {
+        let mut value = self.borrow_mut();
+        value.tracked = true;
+        value
+    }
+
+    /// Mutably borrows the wrapped value for the duration of `f`.
+    ///
+    /// This is `f(&mut cell.borrow_mut())`, which ends the borrow as soon as
+    /// `f` returns, so that it can't be held by mistake while borrowing the
+    /// `GcCell` again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed, or if `f` tries to borrow
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let c = GcCell::new(5);
+    /// c.update(|v| *v += 1);
+    /// assert_eq!(*c.borrow(), 6);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.borrow_mut());
+    }
 }
 
 impl<T: ?Sized> GcCell<T> {
@@ -611,6 +1971,88 @@ impl<T: ?Sized> GcCell<T> {
             })
         }
     }
+
+    /// Immutably borrows the wrapped value without a guard, returning an error
+    /// if the value is currently mutably borrowed.
+    ///
+    /// Unlike [`try_borrow`](#method.try_borrow), this doesn't count as a
+    /// borrow, so the `GcCell` can be mutably borrowed while the returned
+    /// reference is alive.
+    ///
+    /// # Safety
+    ///
+    /// The `GcCell` must not be mutably borrowed while the returned reference
+    /// is alive. Mutably borrowing it then is undefined behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let c = GcCell::new(5);
+    ///
+    /// {
+    ///     let m = c.borrow_mut();
+    ///     assert!(unsafe { c.try_borrow_unguarded() }.is_err());
+    /// }
+    ///
+    /// {
+    ///     let m = c.borrow();
+    ///     assert!(unsafe { c.try_borrow_unguarded() }.is_ok());
+    /// }
+    ///
+    /// assert_eq!(unsafe { c.try_borrow_unguarded() }.unwrap(), &5);
+    /// ```
+    pub unsafe fn try_borrow_unguarded(&self) -> Result<&T, BorrowError> {
+        if self.flags.get().borrowed() == BorrowState::Writing {
+            return Err(BorrowError);
+        }
+        Ok(&*self.cell.get())
+    }
+}
+
+impl<T: ?Sized> GcCell<T> {
+    /// Returns a mutable reference to the wrapped value.
+    ///
+    /// Since this method borrows `GcCell` mutably, it is statically guaranteed
+    /// that no borrows to the underlying data exist, so the borrow flag is
+    /// neither checked nor updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let mut c = GcCell::new(5);
+    /// *c.get_mut() += 1;
+    ///
+    /// assert_eq!(*c.borrow(), 6);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Returns whether the value was mutably borrowed through
+    /// [`borrow_mut_tracked`](#method.borrow_mut_tracked) since the last call,
+    /// and clears the flag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::GcCell;
+    ///
+    /// let c = GcCell::new(5);
+    /// *c.borrow_mut_tracked() += 1;
+    ///
+    /// assert!(c.take_dirty());
+    /// assert!(!c.take_dirty());
+    /// ```
+    pub fn take_dirty(&self) -> bool {
+        let flags = self.flags.get();
+        self.flags.set(flags.set_dirty(false));
+        flags.dirty()
+    }
 }
 
 impl<T: Trace + ?Sized> GcCell<T> {
@@ -635,25 +2077,80 @@ impl<T: Trace + ?Sized> GcCell<T> {
     ///
     /// assert!(c.try_borrow_mut().is_ok());
     /// ```
+    #[cfg_attr(feature = "debug-borrows", track_caller)]
     pub fn try_borrow_mut(&self) -> Result<GcCellRefMut<'_, T>, BorrowMutError> {
         if self.flags.get().borrowed() != BorrowState::Unused {
             return Err(BorrowMutError);
         }
         self.flags.set(self.flags.get().set_writing());
+        #[cfg(feature = "debug-borrows")]
+        {
+            let taken = std::panic::Location::caller();
+            let _ = MUT_BORROWS.try_with(|b| b.borrow_mut().push((cell_addr(self), taken)));
+        }
 
         unsafe {
             // Force the val_ref's contents to be rooted for the duration of the
             // mutable borrow
-            if !self.flags.get().rooted() {
+            let rooted = !self.flags.get().rooted();
+            if rooted {
                 (*self.cell.get()).root();
+                self.flags.set(self.flags.get().set_rooted(true));
             }
 
             Ok(GcCellRefMut {
                 gc_cell: self,
                 value: &mut *self.cell.get(),
+                tracked: false,
+                rooted,
+            })
+        }
+    }
+}
+
+// The `GcCell`s which are mutably borrowed on this thread, along with where
+// each borrow was taken, innermost last.
+#[cfg(feature = "debug-borrows")]
+thread_local!(static MUT_BORROWS: RefCell<Vec<(usize, &'static std::panic::Location<'static>)>> = const { RefCell::new(Vec::new()) });
+
+#[cfg(feature = "debug-borrows")]
+fn cell_addr<T: ?Sized>(cell: &GcCell<T>) -> usize {
+    (cell as *const GcCell<T>).cast::<u8>() as usize
+}
+
+/// Panics for a borrow of `cell` which conflicts with one already taken.
+///
+/// With the `debug-borrows` feature, the message names the `GcCell`, and
+/// where it was mutably borrowed if it was, which tells a reentrant borrow of
+/// the same cell further up the stack apart from a borrow of another one.
+#[cold]
+#[track_caller]
+fn conflicting_borrow<T: ?Sized>(cell: &GcCell<T>, e: impl Display) -> ! {
+    #[cfg(feature = "debug-borrows")]
+    {
+        let addr = cell_addr(cell);
+        let taken = MUT_BORROWS
+            .try_with(|b| {
+                b.borrow()
+                    .iter()
+                    .rev()
+                    .find(|(c, _)| *c == addr)
+                    .map(|&(_, l)| l)
             })
+            .unwrap_or(None);
+        match taken {
+            Some(taken) => panic!(
+                "reentrant borrow of the same GcCell at {:#x}: {} at {}",
+                addr, e, taken
+            ),
+            None => panic!("reentrant borrow of the same GcCell at {:#x}: {}", addr, e),
         }
     }
+    #[cfg(not(feature = "debug-borrows"))]
+    {
+        let _ = cell;
+        panic!("{}", e)
+    }
 }
 
 /// An error returned by [`GcCell::try_borrow`](struct.GcCell.html#method.try_borrow).
@@ -682,7 +2179,12 @@ unsafe impl<T: Trace + ?Sized> Trace for GcCell<T> {
     #[inline]
     unsafe fn trace(&self) {
         match self.flags.get().borrowed() {
-            BorrowState::Writing => (),
+            // The contents were rooted by `try_borrow_mut`, so they're kept
+            // alive without being traced.
+            BorrowState::Writing => debug_assert!(
+                self.flags.get().rooted(),
+                "traced a mutably borrowed GcCell whose contents aren't rooted"
+            ),
             _ => (*self.cell.get()).trace(),
         }
     }
@@ -717,6 +2219,22 @@ unsafe impl<T: Trace + ?Sized> Trace for GcCell<T> {
             _ => unsafe { (*self.cell.get()).finalize_glue() },
         }
     }
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        match self.flags.get().borrowed() {
+            BorrowState::Writing => false,
+            _ => unsafe { (*self.cell.get()).needs_finalize() },
+        }
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        match self.flags.get().borrowed() {
+            BorrowState::Writing => (),
+            _ => (*self.cell.get()).visit_children(visitor),
+        }
+    }
 }
 
 /// A wrapper type for an immutably borrowed value from a `GcCell<T>`.
@@ -745,6 +2263,33 @@ impl<'a, T: ?Sized> GcCellRef<'a, T> {
         }
     }
 
+    /// Clones the borrowed data, and releases the borrow.
+    ///
+    /// The clone is a snapshot: later changes to the `GcCell` aren't
+    /// reflected in it.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `GcCellRef::into_owned(...)`, like [`clone`](#method.clone).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gc::{GcCell, GcCellRef};
+    ///
+    /// let c = GcCell::new((5, 'b'));
+    /// let b = GcCellRef::map(c.borrow(), |t| &t.1);
+    /// let owned: char = GcCellRef::into_owned(b);
+    /// *c.borrow_mut() = (6, 'c');
+    /// assert_eq!(owned, 'b');
+    /// ```
+    #[inline]
+    pub fn into_owned(orig: GcCellRef<'a, T>) -> T
+    where
+        T: Clone,
+    {
+        orig.value.clone()
+    }
+
     /// Makes a new `GcCellRef` for a component of the borrowed data.
     ///
     /// The `GcCell` is already immutably borrowed, so this cannot fail.
@@ -761,7 +2306,7 @@ impl<'a, T: ?Sized> GcCellRef<'a, T> {
     /// let c = GcCell::new((5, 'b'));
     /// let b1: GcCellRef<(u32, char)> = c.borrow();
     /// let b2: GcCellRef<u32> = GcCellRef::map(b1, |t| &t.0);
-    /// assert_eq!(*b2, 5);
+    /// assert_eq!(b2, 5);
     /// ```
     #[inline]
     pub fn map<U, F>(orig: Self, f: F) -> GcCellRef<'a, U>
@@ -884,15 +2429,43 @@ impl<'a, T: ?Sized> Drop for GcCellRef<'a, T> {
     }
 }
 
-impl<'a, T: ?Sized + Debug> Debug for GcCellRef<'a, T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Debug::fmt(&**self, f)
+impl<'a, T: ?Sized + Debug> Debug for GcCellRef<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: ?Sized + Display> Display for GcCellRef<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: ?Sized + PartialEq<U>, U: ?Sized> PartialEq<U> for GcCellRef<'a, T> {
+    #[inline]
+    fn eq(&self, other: &U) -> bool {
+        **self == *other
+    }
+}
+
+impl<'a, T: ?Sized + PartialOrd<U>, U: ?Sized> PartialOrd<U> for GcCellRef<'a, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &U) -> Option<Ordering> {
+        (**self).partial_cmp(other)
+    }
+}
+
+impl<'a, T: ?Sized + AsRef<U>, U: ?Sized> AsRef<U> for GcCellRef<'a, T> {
+    #[inline]
+    fn as_ref(&self) -> &U {
+        (**self).as_ref()
     }
 }
 
-impl<'a, T: ?Sized + Display> Display for GcCellRef<'a, T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Display::fmt(&**self, f)
+impl<'a, T: ?Sized> std::borrow::Borrow<T> for GcCellRef<'a, T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
     }
 }
 
@@ -900,13 +2473,20 @@ impl<'a, T: ?Sized + Display> Display for GcCellRef<'a, T> {
 pub struct GcCellRefMut<'a, T: Trace + ?Sized + 'static, U: ?Sized = T> {
     gc_cell: &'a GcCell<T>,
     value: &'a mut U,
+    // Whether to mark the `GcCell` dirty when dropped.
+    tracked: bool,
+    // Whether the contents were rooted for this borrow, and are to be
+    // unrooted when it's dropped.
+    rooted: bool,
 }
 
 impl<'a, T: Trace + ?Sized, U: ?Sized> GcCellRefMut<'a, T, U> {
     /// Makes a new `GcCellRefMut` for a component of the borrowed data, e.g., an enum
     /// variant.
     ///
-    /// The `GcCell` is already mutably borrowed, so this cannot fail.
+    /// The `GcCell` is already mutably borrowed, so this cannot fail. The
+    /// whole contents of the `GcCell` stay rooted until the returned
+    /// `GcCellRefMut` is dropped, not just the component.
     ///
     /// This is an associated function that needs to be used as
     /// `GcCellRefMut::map(...)`. A method would interfere with methods of the same
@@ -921,7 +2501,7 @@ impl<'a, T: Trace + ?Sized, U: ?Sized> GcCellRefMut<'a, T, U> {
     /// {
     ///     let b1: GcCellRefMut<(u32, char)> = c.borrow_mut();
     ///     let mut b2: GcCellRefMut<(u32, char), u32> = GcCellRefMut::map(b1, |t| &mut t.0);
-    ///     assert_eq!(*b2, 5);
+    ///     assert_eq!(b2, 5);
     ///     *b2 = 42;
     /// }
     /// assert_eq!(*c.borrow(), (42, 'b'));
@@ -933,6 +2513,8 @@ impl<'a, T: Trace + ?Sized, U: ?Sized> GcCellRefMut<'a, T, U> {
         F: FnOnce(&mut U) -> &mut V,
     {
         let gc_cell = orig.gc_cell;
+        let tracked = orig.tracked;
+        let rooted = orig.rooted;
 
         // Use MaybeUninit to avoid calling the destructor of
         // GcCellRefMut (which would update the borrow flags) and to
@@ -944,6 +2526,8 @@ impl<'a, T: Trace + ?Sized, U: ?Sized> GcCellRefMut<'a, T, U> {
         GcCellRefMut {
             gc_cell,
             value: f(value),
+            tracked,
+            rooted,
         }
     }
 
@@ -982,6 +2566,8 @@ impl<'a, T: Trace + ?Sized, U: ?Sized> GcCellRefMut<'a, T, U> {
         F: FnOnce(&mut U) -> Option<&mut V>,
     {
         let gc_cell = orig.gc_cell;
+        let tracked = orig.tracked;
+        let rooted = orig.rooted;
 
         // Use MaybeUninit to avoid calling the destructor of
         // GcCellRefMut (which would update the borrow flags) and to
@@ -992,7 +2578,12 @@ impl<'a, T: Trace + ?Sized, U: ?Sized> GcCellRefMut<'a, T, U> {
 
         match f(value) {
             None => Err(unsafe { orig.assume_init() }),
-            Some(value) => Ok(GcCellRefMut { gc_cell, value }),
+            Some(value) => Ok(GcCellRefMut {
+                gc_cell,
+                value,
+                tracked,
+                rooted,
+            }),
         }
     }
 }
@@ -1019,14 +2610,25 @@ impl<'a, T: Trace + ?Sized, U: ?Sized> Drop for GcCellRefMut<'a, T, U> {
         debug_assert!(self.gc_cell.flags.get().borrowed() == BorrowState::Writing);
         // Restore the rooted state of the GcCell's contents to the state of the GcCell.
         // During the lifetime of the GcCellRefMut, the GcCell's contents are rooted.
-        if !self.gc_cell.flags.get().rooted() {
+        if self.rooted {
             unsafe {
+                crate::gc::write_barrier(&*self.gc_cell.cell.get());
                 (*self.gc_cell.cell.get()).unroot();
             }
         }
+        let flags = self.gc_cell.flags.get().set_unused();
+        let flags = flags.set_rooted(flags.rooted() && !self.rooted);
         self.gc_cell
             .flags
-            .set(self.gc_cell.flags.get().set_unused());
+            .set(flags.set_dirty(flags.dirty() || self.tracked));
+        #[cfg(feature = "debug-borrows")]
+        let _ = MUT_BORROWS.try_with(|b| {
+            let mut b = b.borrow_mut();
+            let addr = cell_addr(self.gc_cell);
+            if let Some(i) = b.iter().rposition(|(c, _)| *c == addr) {
+                b.remove(i);
+            }
+        });
     }
 }
 
@@ -1042,7 +2644,37 @@ impl<'a, T: Trace + ?Sized, U: Display + ?Sized> Display for GcCellRefMut<'a, T,
     }
 }
 
-unsafe impl<T: ?Sized + Send> Send for GcCell<T> {}
+impl<'a, T: Trace + ?Sized, U: PartialEq<V> + ?Sized, V: ?Sized> PartialEq<V>
+    for GcCellRefMut<'a, T, U>
+{
+    #[inline]
+    fn eq(&self, other: &V) -> bool {
+        **self == *other
+    }
+}
+
+impl<'a, T: Trace + ?Sized, U: PartialOrd<V> + ?Sized, V: ?Sized> PartialOrd<V>
+    for GcCellRefMut<'a, T, U>
+{
+    #[inline]
+    fn partial_cmp(&self, other: &V) -> Option<Ordering> {
+        (**self).partial_cmp(other)
+    }
+}
+
+impl<'a, T: Trace + ?Sized, U: AsRef<V> + ?Sized, V: ?Sized> AsRef<V> for GcCellRefMut<'a, T, U> {
+    #[inline]
+    fn as_ref(&self) -> &V {
+        (**self).as_ref()
+    }
+}
+
+impl<'a, T: Trace + ?Sized, U: ?Sized> std::borrow::Borrow<U> for GcCellRefMut<'a, T, U> {
+    #[inline]
+    fn borrow(&self) -> &U {
+        self
+    }
+}
 
 impl<T: Clone> Clone for GcCell<T> {
     #[inline]
@@ -1058,6 +2690,11 @@ impl<T: Default> Default for GcCell<T> {
     }
 }
 
+/// Compares the values of the cells.
+///
+/// # Panics
+///
+/// Panics if either value is currently mutably borrowed.
 impl<T: ?Sized + PartialEq> PartialEq for GcCell<T> {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
@@ -1101,6 +2738,23 @@ impl<T: ?Sized + Ord> Ord for GcCell<T> {
     }
 }
 
+/// Hashes the value of the cell, consistently with `PartialEq`.
+///
+/// # Panics
+///
+/// Panics if the value is currently mutably borrowed, as comparing it does.
+/// Unlike `Debug`, which shows `<borrowed>` then, hashing can't stand in a
+/// placeholder for the value: a cell hashed while borrowed would be looked
+/// for in the wrong place in a `HashMap`.
+impl<T: ?Sized + Hash> Hash for GcCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.try_borrow() {
+            Ok(value) => value.hash(state),
+            Err(_) => panic!("hashed a GcCell while it is mutably borrowed"),
+        }
+    }
+}
+
 impl<T: ?Sized + Debug> Debug for GcCell<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.flags.get().borrowed() {
@@ -1116,6 +2770,471 @@ impl<T: ?Sized + Debug> Debug for GcCell<T> {
     }
 }
 
+/// A cell which can be written to only once, that can be used inside of a
+/// garbage-collected pointer.
+///
+/// Unlike a `GcCell<Option<T>>`, once the value is set it can't be replaced,
+/// so shared references to it stay valid for as long as the cell itself.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, GcOnceCell};
+///
+/// let cell = Gc::new(GcOnceCell::new());
+/// assert!(cell.get().is_none());
+///
+/// let value: &Gc<i32> = cell.get_or_init(|| Gc::new(92));
+/// assert_eq!(**value, 92);
+/// assert!(cell.set(Gc::new(0)).is_err());
+/// ```
+pub struct GcOnceCell<T: 'static> {
+    rooted: Cell<bool>,
+    initializing: Cell<bool>,
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<T> GcOnceCell<T> {
+    /// Creates a new empty `GcOnceCell`.
+    #[inline]
+    pub fn new() -> Self {
+        GcOnceCell {
+            rooted: Cell::new(true),
+            initializing: Cell::new(false),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// Returns a reference to the value, or `None` if the cell is empty.
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        unsafe { (*self.value.get()).as_ref() }
+    }
+
+    /// Consumes the `GcOnceCell`, returning the wrapped value, if any.
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Trace> GcOnceCell<T> {
+    /// Sets the value of the cell, or returns `value` back if the cell is
+    /// already full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from the initializer passed to
+    /// [`get_or_init`](#method.get_or_init).
+    #[track_caller]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        assert!(
+            !self.initializing.get(),
+            "GcOnceCell initialized reentrantly"
+        );
+        if self.get().is_some() {
+            return Err(value);
+        }
+        unsafe { self.fill(value) };
+        Ok(())
+    }
+
+    /// Returns a reference to the value, initializing it with `f` if the cell
+    /// is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` tries to initialize the cell itself, through `set` or
+    /// `get_or_init`. If `f` panics, the cell is left empty.
+    #[track_caller]
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        if let Some(value) = self.get() {
+            return value;
+        }
+        assert!(
+            !self.initializing.get(),
+            "GcOnceCell initialized reentrantly"
+        );
+
+        struct Initializing<'a>(&'a Cell<bool>);
+        impl Drop for Initializing<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        self.initializing.set(true);
+        let guard = Initializing(&self.initializing);
+        // The new value is rooted until it is stored, so collections
+        // triggered by `f` can't free it.
+        let value = f();
+        drop(guard);
+
+        unsafe {
+            self.fill(value);
+            (*self.value.get()).as_ref().unwrap()
+        }
+    }
+
+    /// Stores `value` in the empty cell, matching its rooted state to the
+    /// cell's.
+    unsafe fn fill(&self, value: T) {
+        debug_assert!(self.get().is_none());
+        if !self.rooted.get() {
+            value.unroot();
+        }
+        let value = (*self.value.get()).insert(value);
+        crate::gc::write_barrier(value);
+    }
+}
+
+impl<T> Finalize for GcOnceCell<T> {}
+
+unsafe impl<T: Trace> Trace for GcOnceCell<T> {
+    #[inline]
+    unsafe fn trace(&self) {
+        if let Some(value) = self.get() {
+            value.trace();
+        }
+    }
+
+    #[inline]
+    unsafe fn root(&self) {
+        assert!(!self.rooted.get(), "Can't root a GcOnceCell twice!");
+        self.rooted.set(true);
+        if let Some(value) = self.get() {
+            value.root();
+        }
+    }
+
+    #[inline]
+    unsafe fn unroot(&self) {
+        assert!(self.rooted.get(), "Can't unroot a GcOnceCell twice!");
+        self.rooted.set(false);
+        if let Some(value) = self.get() {
+            value.unroot();
+        }
+    }
+
+    #[inline]
+    fn finalize_glue(&self) {
+        Finalize::finalize(self);
+        if let Some(value) = self.get() {
+            value.finalize_glue();
+        }
+    }
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        self.get().is_some_and(Trace::needs_finalize)
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        if let Some(value) = self.get() {
+            value.visit_children(visitor);
+        }
+    }
+}
+
+impl<T> Default for GcOnceCell<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for GcOnceCell<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        GcOnceCell {
+            rooted: Cell::new(true),
+            initializing: Cell::new(false),
+            value: UnsafeCell::new(Some(value)),
+        }
+    }
+}
+
+impl<T: Clone> Clone for GcOnceCell<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        GcOnceCell {
+            rooted: Cell::new(true),
+            initializing: Cell::new(false),
+            value: UnsafeCell::new(self.get().cloned()),
+        }
+    }
+}
+
+impl<T: Debug> Debug for GcOnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("GcOnceCell").field(value).finish(),
+            None => f.write_str("GcOnceCell(<uninit>)"),
+        }
+    }
+}
+
+///////////
+// GcVec //
+///////////
+
+/// A growable vector on the garbage-collected heap, which can be pushed to
+/// and popped from through a shared reference.
+///
+/// This is a `Gc<GcCell<Vec<T>>>` which borrows the cell for each operation,
+/// so that `vec.push(x)` can be written in place of
+/// `vec.borrow_mut().push(x)`. Cloning a `GcVec` returns another reference
+/// to the same vector, as with `Gc`.
+///
+/// `GcVec` doesn't implement `Index`, as a reference into the vector could
+/// be invalidated by a later `push`: use [`get`](#method.get), or
+/// [`borrow`](#method.borrow) to hold a borrow of the whole vector.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, GcVec};
+///
+/// let stack = GcVec::new();
+/// stack.push(Gc::new(1));
+/// stack.push(Gc::new(2));
+/// gc::force_collect();
+///
+/// assert_eq!(stack.len(), 2);
+/// assert_eq!(stack.get(0).map(|v| *v), Some(1));
+/// assert_eq!(stack.pop().map(|v| *v), Some(2));
+/// assert_eq!(stack.iter().map(|v| *v).collect::<Vec<_>>(), [1]);
+/// ```
+pub struct GcVec<T: Trace + 'static>(Gc<GcCell<Vec<T>>>);
+
+impl<T: Trace> GcVec<T> {
+    /// Constructs a new, empty `GcVec`.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    pub fn new() -> Self {
+        GcVec(Gc::new(GcCell::new(Vec::new())))
+    }
+
+    /// Appends an element to the back of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed.
+    #[track_caller]
+    pub fn push(&self, value: T) {
+        self.0.borrow_mut().push(value);
+    }
+
+    /// Removes the last element from the vector and returns it, or `None` if
+    /// it is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed.
+    #[track_caller]
+    pub fn pop(&self) -> Option<T> {
+        self.0.borrow_mut().pop()
+    }
+
+    /// Returns a clone of the element at `index`, or `None` if it is out of
+    /// bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently mutably borrowed.
+    #[track_caller]
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.0.borrow().get(index).cloned()
+    }
+
+    /// Returns the number of elements in the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently mutably borrowed.
+    #[track_caller]
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently mutably borrowed.
+    #[track_caller]
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Returns an iterator over clones of the elements.
+    ///
+    /// The vector isn't borrowed between elements, so it can be modified
+    /// while iterating: the iterator ends once it reaches the current length.
+    pub fn iter(&self) -> GcVecIter<'_, T>
+    where
+        T: Clone,
+    {
+        GcVecIter {
+            vec: self,
+            index: 0,
+        }
+    }
+
+    /// Immutably borrows the whole vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently mutably borrowed.
+    #[track_caller]
+    pub fn borrow(&self) -> GcCellRef<'_, Vec<T>> {
+        self.0.borrow()
+    }
+
+    /// Mutably borrows the whole vector, e.g. to call other `Vec` methods.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed.
+    #[track_caller]
+    pub fn borrow_mut(&self) -> GcCellRefMut<'_, Vec<T>> {
+        self.0.borrow_mut()
+    }
+}
+
+impl<T: Trace> Clone for GcVec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        GcVec(self.0.clone())
+    }
+}
+
+impl<T: Trace> Default for GcVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trace> From<Vec<T>> for GcVec<T> {
+    fn from(vec: Vec<T>) -> Self {
+        GcVec(Gc::new(GcCell::new(vec)))
+    }
+}
+
+impl<T: Trace> FromIterator<T> for GcVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: Trace + Debug> Debug for GcVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GcVec").field(&self.0).finish()
+    }
+}
+
+impl<T: Trace> Finalize for GcVec<T> {}
+
+unsafe impl<T: Trace> Trace for GcVec<T> {
+    custom_trace!(this, {
+        mark(&this.0);
+    });
+}
+
+impl<'a, T: Trace + Clone> IntoIterator for &'a GcVec<T> {
+    type Item = T;
+    type IntoIter = GcVecIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> GcVecIter<'a, T> {
+        self.iter()
+    }
+}
+
+/// An iterator over clones of the elements of a [`GcVec`], returned by
+/// [`GcVec::iter`].
+pub struct GcVecIter<'a, T: Trace + 'static> {
+    vec: &'a GcVec<T>,
+    index: usize,
+}
+
+impl<T: Trace + Clone> Iterator for GcVecIter<'_, T> {
+    type Item = T;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<T> {
+        let value = self.vec.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
+
+/////////////////
+// HeaderSlice //
+/////////////////
+
+/// A header followed by a slice, stored in a single garbage-collected
+/// allocation by [`Gc::new_dst_with`].
+///
+/// This avoids the second allocation, and the extra indirection, of a
+/// `Gc<(H, Box<[E]>)>`, e.g. for the string and array objects of an
+/// interpreter. The allocation also records its size and the length of the
+/// slice, for the collector, which takes two `usize`s.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, HeaderSlice};
+///
+/// let s: Gc<HeaderSlice<u32, u8>> = Gc::new_dst_with(7, b"abc");
+/// assert_eq!(s.header, 7);
+/// assert_eq!(&s.slice, b"abc");
+/// ```
+#[repr(C)]
+pub struct HeaderSlice<H, E> {
+    meta: DstMeta,
+    /// The fixed-size part.
+    pub header: H,
+    /// The variable-length part.
+    pub slice: [E],
+}
+
+impl<H: Trace + 'static, E: Trace + Clone + 'static> Gc<HeaderSlice<H, E>> {
+    /// Constructs a new `Gc<HeaderSlice<H, E>>` holding `header` followed by
+    /// clones of the elements of `slice`, in a single allocation.
+    ///
+    /// # Collection
+    ///
+    /// This method could trigger a garbage collection.
+    pub fn new_dst_with(header: H, slice: &[E]) -> Self {
+        unsafe { Gc::from_gcbox(GcBox::new_dst(header, slice)) }
+    }
+}
+
+impl<H, E> Finalize for HeaderSlice<H, E> {}
+unsafe impl<H: Trace, E: Trace> Trace for HeaderSlice<H, E> {
+    custom_trace!(this, {
+        mark(&this.header);
+        mark(&this.slice);
+    });
+}
+
+impl<H: Debug, E: Debug> Debug for HeaderSlice<H, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeaderSlice")
+            .field("header", &self.header)
+            .field("slice", &&self.slice)
+            .finish()
+    }
+}
+
 // Sets the data pointer of a `?Sized` raw pointer.
 //
 // For a slice/trait object, this sets the `data` field and leaves the rest
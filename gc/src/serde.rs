@@ -1,5 +1,12 @@
-use crate::{Gc, Trace};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::{custom_trace, Finalize, Gc, GcCell, GcPointer, Trace};
+use serde::de::{self, EnumAccess, Unexpected, VariantAccess, Visitor};
+use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::hash_map::{Entry, HashMap};
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::mem;
 
 impl<'de, T: Deserialize<'de> + Trace> Deserialize<'de> for Gc<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -10,6 +17,9 @@ impl<'de, T: Deserialize<'de> + Trace> Deserialize<'de> for Gc<T> {
     }
 }
 
+/// Serializes the value of the `Gc`. An allocation reachable through several
+/// `Gc`s is written once for each of them, and deserialized as separate
+/// allocations: use [`SharedGc`] to keep it shared.
 impl<T: Serialize> Serialize for Gc<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -18,3 +28,282 @@ impl<T: Serialize> Serialize for Gc<T> {
         T::serialize(self, serializer)
     }
 }
+
+impl<'de, T: Deserialize<'de> + Trace> Deserialize<'de> for GcCell<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(GcCell::new)
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for GcCell<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.try_borrow() {
+            Ok(value) => T::serialize(&value, serializer),
+            Err(e) => Err(S::Error::custom(e)),
+        }
+    }
+}
+
+/// A `Gc` which is serialized so that allocations shared between several
+/// `SharedGc`s stay shared when deserialized.
+///
+/// The first time an allocation is serialized, its value is written as the
+/// `Def` variant of an enum. Later occurrences are written as `Ref(n)`,
+/// where `n` is the number of allocations defined before it. Deserializing a
+/// `Ref` then returns a clone of the `Gc` deserialized from that `Def`.
+///
+/// Allocations are numbered from the start of the outermost `SharedGc`
+/// being serialized or deserialized, or of the enclosing [`with_sharing`]
+/// call, which allows sharing between `SharedGc`s which aren't nested in one
+/// another.
+///
+/// Serializing a cycle terminates, but cycles can't be deserialized: the
+/// `Gc` for a `Def` can only be allocated once its value is complete, so a
+/// `Ref` to it from within its value is an error.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{Gc, SharedGc};
+///
+/// let leaf = Gc::new(1);
+/// let pair = SharedGc(Gc::new(vec![SharedGc(leaf.clone()), SharedGc(leaf)]));
+/// let json = serde_json::to_string(&pair).unwrap();
+/// assert_eq!(json, r#"{"Def":[{"Def":1},{"Ref":1}]}"#);
+///
+/// let pair: SharedGc<Vec<SharedGc<i32>>> = serde_json::from_str(&json).unwrap();
+/// assert!(Gc::ptr_eq(&pair.0[0].0, &pair.0[1].0));
+/// ```
+pub struct SharedGc<T: ?Sized + 'static>(pub Gc<T>);
+
+/// Numbers the allocations serialized or deserialized by `SharedGc`s within
+/// `f`, so that they stay shared between several `SharedGc`s which aren't
+/// nested in one another.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{with_sharing, Gc, SharedGc};
+///
+/// let leaf = Gc::new(1);
+/// let (a, b) = with_sharing(|| {
+///     let a = serde_json::to_string(&SharedGc(leaf.clone())).unwrap();
+///     let b = serde_json::to_string(&SharedGc(leaf.clone())).unwrap();
+///     (a, b)
+/// });
+/// assert_eq!((&*a, &*b), (r#"{"Def":1}"#, r#"{"Ref":0}"#));
+///
+/// let (a, b): (SharedGc<i32>, SharedGc<i32>) = with_sharing(|| {
+///     (serde_json::from_str(&a).unwrap(), serde_json::from_str(&b).unwrap())
+/// });
+/// assert!(Gc::ptr_eq(&a.0, &b.0));
+/// ```
+pub fn with_sharing<R>(f: impl FnOnce() -> R) -> R {
+    let _scope = Scope::enter();
+    f()
+}
+
+#[derive(Default)]
+struct Sharing {
+    // The number of `with_sharing` calls and `SharedGc`s being serialized or
+    // deserialized. The tables are cleared when it drops back to zero.
+    depth: usize,
+    // The number of each allocation serialized so far, and a `Gc<T>` keeping
+    // it alive so that its address isn't reused.
+    serialized: HashMap<GcPointer, (u64, Box<dyn Any>)>,
+    // The `Gc<T>` deserialized for each `Def` so far, or `None` while its
+    // value is being deserialized.
+    deserialized: Vec<Option<Box<dyn Any>>>,
+}
+
+thread_local!(static SHARING: RefCell<Sharing> = RefCell::new(Sharing::default()));
+
+struct Scope;
+
+impl Scope {
+    fn enter() -> Scope {
+        SHARING.with(|s| s.borrow_mut().depth += 1);
+        Scope
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let tables = SHARING.with(|s| {
+            let mut s = s.borrow_mut();
+            s.depth -= 1;
+            if s.depth == 0 {
+                Some((mem::take(&mut s.serialized), mem::take(&mut s.deserialized)))
+            } else {
+                None
+            }
+        });
+        // Drop the `Gc`s outside of the borrow.
+        drop(tables);
+    }
+}
+
+const VARIANTS: &[&str] = &["Def", "Ref"];
+
+impl<T: Serialize + ?Sized> Serialize for SharedGc<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let _scope = Scope::enter();
+        let seen = SHARING.with(|s| {
+            let mut s = s.borrow_mut();
+            let next = s.serialized.len() as u64;
+            match s.serialized.entry(GcPointer::from(&self.0)) {
+                Entry::Occupied(entry) => Some(entry.get().0),
+                Entry::Vacant(entry) => {
+                    entry.insert((next, Box::new(self.0.clone())));
+                    None
+                }
+            }
+        });
+        match seen {
+            Some(n) => serializer.serialize_newtype_variant("SharedGc", 1, VARIANTS[1], &n),
+            None => serializer.serialize_newtype_variant("SharedGc", 0, VARIANTS[0], &*self.0),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de> + Trace> Deserialize<'de> for SharedGc<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let _scope = Scope::enter();
+        deserializer
+            .deserialize_enum("SharedGc", VARIANTS, SharedGcVisitor(PhantomData))
+            .map(SharedGc)
+    }
+}
+
+struct SharedGcVisitor<T: 'static>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de> + Trace> Visitor<'de> for SharedGcVisitor<T> {
+    type Value = Gc<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a shared Gc")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Gc<T>, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        match data.variant()? {
+            (Variant::Def, variant) => {
+                let n = SHARING.with(|s| {
+                    let mut s = s.borrow_mut();
+                    s.deserialized.push(None);
+                    s.deserialized.len() - 1
+                });
+                let gc = Gc::new(variant.newtype_variant::<T>()?);
+                SHARING.with(|s| s.borrow_mut().deserialized[n] = Some(Box::new(gc.clone())));
+                Ok(gc)
+            }
+            (Variant::Ref, variant) => {
+                let n: u64 = variant.newtype_variant()?;
+                SHARING.with(|s| match s.borrow().deserialized.get(n as usize) {
+                    Some(Some(gc)) => gc.downcast_ref::<Gc<T>>().cloned().ok_or_else(|| {
+                        de::Error::custom(format_args!("shared Gc {} has a different type", n))
+                    }),
+                    Some(None) => Err(de::Error::custom(format_args!(
+                        "shared Gc {} is referenced from its own value, \
+                         but cycles can't be deserialized",
+                        n
+                    ))),
+                    None => Err(de::Error::custom(format_args!(
+                        "shared Gc {} is referenced before it is defined",
+                        n
+                    ))),
+                })
+            }
+        }
+    }
+}
+
+enum Variant {
+    Def,
+    Ref,
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VariantVisitor;
+
+        impl<'de> Visitor<'de> for VariantVisitor {
+            type Value = Variant;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("`Def` or `Ref`")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Variant, E> {
+                match value {
+                    0 => Ok(Variant::Def),
+                    1 => Ok(Variant::Ref),
+                    _ => Err(E::invalid_value(Unexpected::Unsigned(value), &self)),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Variant, E> {
+                match value {
+                    "Def" => Ok(Variant::Def),
+                    "Ref" => Ok(Variant::Ref),
+                    _ => Err(E::unknown_variant(value, VARIANTS)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(VariantVisitor)
+    }
+}
+
+impl<T: ?Sized> Clone for SharedGc<T> {
+    fn clone(&self) -> Self {
+        SharedGc(self.0.clone())
+    }
+}
+
+impl<T: Debug + ?Sized> Debug for SharedGc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedGc").field(&self.0).finish()
+    }
+}
+
+impl<T: ?Sized> From<Gc<T>> for SharedGc<T> {
+    fn from(gc: Gc<T>) -> Self {
+        SharedGc(gc)
+    }
+}
+
+impl<T: ?Sized> Finalize for SharedGc<T> {}
+
+unsafe impl<T: Trace + ?Sized> Trace for SharedGc<T> {
+    custom_trace!(this, {
+        mark(&this.0);
+    });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        self.0.visit_children(visitor);
+    }
+}
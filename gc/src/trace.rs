@@ -1,41 +1,189 @@
+use crate::GcPointer;
 use std::borrow::{Cow, ToOwned};
+use std::cmp::{Ordering, Reverse};
 use std::collections::hash_map::{DefaultHasher, RandomState};
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::ffi::{CStr, CString, OsStr, OsString};
 use std::hash::BuildHasherDefault;
 #[allow(deprecated)]
 use std::hash::SipHasher;
 use std::marker::PhantomData;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::num::{
     NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
-    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
 };
+use std::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::pin::Pin;
+use std::rc::{self, Rc};
 use std::sync::atomic::{
     AtomicBool, AtomicI16, AtomicI32, AtomicI64, AtomicI8, AtomicIsize, AtomicU16, AtomicU32,
     AtomicU64, AtomicU8, AtomicUsize,
 };
+use std::sync::{self, Arc};
+use std::time::{Duration, Instant, SystemTime};
 
 /// The Finalize trait, which needs to be implemented on
 /// garbage-collected objects to define finalization logic.
+///
+/// When a collection finds unreachable objects, it finalizes all of them
+/// before freeing any, so finalizers may still use the other objects they
+/// reference. They are finalized in reverse allocation order: the most
+/// recently allocated object is finalized first.
+///
+/// A finalizer may resurrect objects by making them reachable again, e.g. by
+/// storing a `Gc` in a thread-local. They are then kept alive, and by default
+/// are not finalized again when they later become unreachable; see
+/// `GcConfig::refinalize_resurrected`.
 pub trait Finalize {
     fn finalize(&self) {}
+
+    /// Returns whether `finalize` may do anything.
+    ///
+    /// This lets the collector skip finalizing objects which don't need it.
+    /// `#[derive(Finalize)]` overrides it to return `false`; leave it alone
+    /// when implementing `finalize` by hand.
+    #[inline]
+    fn has_finalizer(&self) -> bool {
+        true
+    }
 }
 
 /// The Trace trait, which needs to be implemented on garbage-collected objects.
+///
+/// Shared-ownership and locking types from std, such as `Rc<T>`, `Arc<T>`,
+/// `Mutex<T>` and `RwLock<T>`, deliberately do not implement `Trace` for
+/// arbitrary `T`. Unrooting the contents of an `Rc` or `Arc` when one clone
+/// moves into a `Gc` would leave every other clone holding unrooted `Gc`s,
+/// which may be freed while still reachable through them. A locked `Mutex`
+/// or `RwLock` can't be traced through at all, and skipping it would let the
+/// collector free its contents. Use `Gc` and `GcCell` for shared and
+/// mutable data on the garbage-collected heap instead, e.g. `Gc<Box<[T]>>`
+/// in place of `Rc<[T]>`. `Rc<str>` and `Arc<str>` are supported, as they
+/// can't contain a `Gc`.
+///
+/// `rc::Weak<T>` and `sync::Weak<T>` are supported for any `T`, with empty
+/// trace methods, e.g. for back-pointers out of the garbage-collected heap.
+/// They can't keep their target alive, so the target is not part of the GC
+/// graph: any `Gc`s in it stay rooted as long as its strong references live.
+///
+/// Likewise, `OnceCell<T>` and `OnceLock<T>` don't implement `Trace`. They
+/// can be filled through a shared reference while already inside a `Gc`,
+/// leaving their new contents rooted: such `Gc`s could never be collected,
+/// and rooting the cell again would panic. Use `GcOnceCell` instead, which
+/// unroots the value as it is stored.
+///
+/// # Trait objects
+///
+/// A trait with `Trace` as a supertrait needs no extra impls to be used as a
+/// trait object: `dyn MyTrait` implements `Trace` through the supertrait, so
+/// `Box<dyn MyTrait>`, and `Box<dyn MyTrait + 'a>` for any lifetime, are
+/// traced through the impl for `Box<T>`.
+///
+/// ```
+/// use gc::{Finalize, Gc, Trace};
+///
+/// trait Value: Trace {
+///     fn get(&self) -> i32;
+/// }
+///
+/// #[derive(Trace, Finalize)]
+/// struct Int(Gc<i32>);
+///
+/// impl Value for Int {
+///     fn get(&self) -> i32 {
+///         *self.0
+///     }
+/// }
+///
+/// let v: Gc<Box<dyn Value>> = Gc::new(Box::new(Int(Gc::new(7))));
+/// gc::force_collect();
+/// assert_eq!(v.get(), 7);
+/// ```
+///
+/// # Closures
+///
+/// Closures, and trait objects such as `Box<dyn Fn()>`, don't implement
+/// `Trace`, so storing one in a `Gc` fails to compile. The `Gc`s a closure
+/// captures can't be traced: they would stay rooted, leaking any cycle they
+/// are part of. Plain `fn` pointers capture nothing and do implement `Trace`.
+///
+/// ```compile_fail
+/// use gc::Gc;
+///
+/// let callback: Gc<Box<dyn Fn() -> i32>> = Gc::new(Box::new(|| 1));
+/// ```
+///
+/// Use [`TracedFn`](crate::TracedFn) for a callback which needs `Gc`s, as it
+/// keeps them in a traced environment. A closure which captures no `Gc`s can
+/// be stored in a field marked `#[unsafe_ignore_trace]`, which makes upholding
+/// that your responsibility.
+///
+/// # Safety
+///
+/// Implementations must visit every `Gc` contained in the value, exactly
+/// once, in each of `trace`, `root` and `unroot`. Missing a `Gc` can cause
+/// it to be collected while still reachable.
 pub unsafe trait Trace: Finalize {
     /// Marks all contained `Gc`s.
+    ///
+    /// # Safety
+    ///
+    /// Only the garbage collector may call this, during the mark phase.
     unsafe fn trace(&self);
 
     /// Increments the root-count of all contained `Gc`s.
+    ///
+    /// # Safety
+    ///
+    /// The contained `Gc`s must currently be unrooted.
     unsafe fn root(&self);
 
     /// Decrements the root-count of all contained `Gc`s.
+    ///
+    /// # Safety
+    ///
+    /// The contained `Gc`s must currently be rooted.
     unsafe fn unroot(&self);
 
     /// Runs `Finalize::finalize()` on this object and all
     /// contained subobjects
     fn finalize_glue(&self);
+
+    /// Returns whether `finalize_glue` may run any finalizer, i.e. whether
+    /// this object or any contained subobject has one.
+    ///
+    /// The collector skips `finalize_glue` for unreachable objects where this
+    /// returns `false`. The default implementation conservatively returns
+    /// `true`; `#[derive(Trace)]` and `unsafe_empty_trace!` compute it from
+    /// `Finalize::has_finalizer`.
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        true
+    }
+
+    /// Calls `visitor` with the address of every contained `Gc`, without
+    /// marking them.
+    ///
+    /// The default implementation runs `trace` in a mode where `Gc`s report
+    /// their address instead of marking, so implementations don't have to
+    /// override it.
+    ///
+    /// The `Gc`s in a mutably borrowed `GcCell` aren't reported, as its
+    /// contents can't be read while the borrow lasts. They are rooted for the
+    /// borrow instead, so they show up as roots rather than as children.
+    ///
+    /// # Safety
+    ///
+    /// The contained `Gc`s must not be dangling, as they may be while the
+    /// collector is sweeping.
+    unsafe fn visit_children(&self, visitor: &mut dyn FnMut(GcPointer)) {
+        for child in crate::gc::record_edges(|| self.trace()) {
+            visitor(child);
+        }
+    }
 }
 
 /// This rule implements the trace methods with empty implementations.
@@ -54,6 +202,12 @@ macro_rules! unsafe_empty_trace {
         fn finalize_glue(&self) {
             $crate::Finalize::finalize(self)
         }
+        #[inline]
+        fn needs_finalize(&self) -> bool {
+            $crate::Finalize::has_finalizer(self)
+        }
+        #[inline]
+        unsafe fn visit_children(&self, _visitor: &mut dyn FnMut($crate::GcPointer)) {}
     };
 }
 
@@ -105,8 +259,78 @@ macro_rules! custom_trace {
     };
 }
 
-impl<T: ?Sized> Finalize for &'static T {}
-unsafe impl<T: ?Sized> Trace for &'static T {
+/// This rule implements the trace methods for an enum, by matching on its
+/// variants and marking the listed fields.
+///
+/// Variants are written as in the enum definition, but with binding names
+/// instead of types for tuple variants: `Tuple(a, b)`, `Struct { x, y }` or
+/// `Unit`. Every variant must be listed. Fields left out of a struct variant
+/// aren't traced, so they must not contain any `Gc`s.
+///
+/// # Examples
+///
+/// ```
+/// use gc::{trace_enum, Finalize, Gc, Trace};
+///
+/// enum Value {
+///     Pair(Gc<i32>, Gc<i32>),
+///     Named { value: Gc<i32>, name: &'static str },
+///     Nil,
+/// }
+///
+/// impl Finalize for Value {}
+/// unsafe impl Trace for Value {
+///     trace_enum!(Value {
+///         Pair(a, b),
+///         Named { value },
+///         Nil,
+///     });
+/// }
+///
+/// let v = Gc::new(Value::Named { value: Gc::new(1), name: "one" });
+/// gc::force_collect();
+/// assert!(matches!(*v, Value::Named { ref value, .. } if **value == 1));
+/// ```
+#[macro_export]
+macro_rules! trace_enum {
+    ($name:ident { $($variants:tt)* }) => {
+        $crate::custom_trace!(this, $crate::trace_enum!(@arms this, $name, [], $($variants)*));
+    };
+    (@arms $this:ident, $name:ident, [$($arms:tt)*], $variant:ident ( $($field:ident),* $(,)? ) $(, $($rest:tt)*)?) => {
+        $crate::trace_enum!(@arms $this, $name, [
+            $($arms)*
+            $name::$variant($(ref $field),*) => { $(mark($field);)* }
+        ], $($($rest)*)?)
+    };
+    (@arms $this:ident, $name:ident, [$($arms:tt)*], $variant:ident { $($field:ident),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::trace_enum!(@arms $this, $name, [
+            $($arms)*
+            $name::$variant { $(ref $field,)* .. } => { $(mark($field);)* }
+        ], $($($rest)*)?)
+    };
+    (@arms $this:ident, $name:ident, [$($arms:tt)*], $variant:ident $(, $($rest:tt)*)?) => {
+        $crate::trace_enum!(@arms $this, $name, [
+            $($arms)*
+            $name::$variant => {}
+        ], $($($rest)*)?)
+    };
+    (@arms $this:ident, $name:ident, [$($arms:tt)*], ) => {
+        match *$this {
+            $($arms)*
+        }
+    };
+}
+
+// A reference doesn't own what it points to, so the `Gc`s behind it are
+// traced by their owner. Only `'static` references can end up in a `Gc`, but
+// values with shorter lifetimes may still be traced, e.g. while rooting them.
+impl<T: ?Sized> Finalize for &T {
+    #[inline]
+    fn has_finalizer(&self) -> bool {
+        false
+    }
+}
+unsafe impl<T: ?Sized> Trace for &T {
     unsafe_empty_trace!();
 }
 
@@ -114,7 +338,12 @@ macro_rules! simple_empty_finalize_trace {
     ($($T:ty),*) => {
         $(
             #[allow(deprecated)]
-            impl Finalize for $T {}
+            impl Finalize for $T {
+                #[inline]
+                fn has_finalizer(&self) -> bool {
+                    false
+                }
+            }
             #[allow(deprecated)]
             unsafe impl Trace for $T { unsafe_empty_trace!(); }
         )*
@@ -142,8 +371,22 @@ simple_empty_finalize_trace![
     String,
     str,
     Rc<str>,
+    Arc<str>,
     Path,
     PathBuf,
+    OsStr,
+    OsString,
+    CStr,
+    CString,
+    Duration,
+    Instant,
+    SystemTime,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
     NonZeroIsize,
     NonZeroUsize,
     NonZeroI8,
@@ -169,7 +412,8 @@ simple_empty_finalize_trace![
     AtomicU64,
     DefaultHasher,
     SipHasher,
-    RandomState
+    RandomState,
+    Ordering
 ];
 
 impl<T, const N: usize> Finalize for [T; N] {}
@@ -179,11 +423,21 @@ unsafe impl<T: Trace, const N: usize> Trace for [T; N] {
             mark(v);
         }
     });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        self.iter().any(Trace::needs_finalize)
+    }
 }
 
 macro_rules! fn_finalize_trace_one {
     ($ty:ty $(,$args:ident)*) => {
-        impl<Ret $(,$args)*> Finalize for $ty {}
+        impl<Ret $(,$args)*> Finalize for $ty {
+            #[inline]
+            fn has_finalizer(&self) -> bool {
+                false
+            }
+        }
         unsafe impl<Ret $(,$args)*> Trace for $ty { unsafe_empty_trace!(); }
     }
 }
@@ -214,6 +468,13 @@ macro_rules! tuple_finalize_trace {
                 let &($(ref $args,)*) = this;
                 $(mark($args);)*
             });
+
+            #[inline]
+            fn needs_finalize(&self) -> bool {
+                #[allow(non_snake_case)]
+                let &($(ref $args,)*) = self;
+                false $(|| $args.needs_finalize())*
+            }
         }
     }
 }
@@ -248,6 +509,33 @@ unsafe impl<T: Trace + ?Sized> Trace for Box<T> {
     custom_trace!(this, {
         mark(&**this);
     });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        (**self).needs_finalize()
+    }
+}
+
+// The contents must not have been dropped with `ManuallyDrop::drop`, as
+// already required to access them.
+impl<T: ?Sized> Finalize for ManuallyDrop<T> {}
+unsafe impl<T: Trace + ?Sized> Trace for ManuallyDrop<T> {
+    custom_trace!(this, {
+        mark(&**this);
+    });
+}
+
+// The contents may not be initialized, so they can't be traced. `Gc`s
+// written into a `MaybeUninit` stay rooted, until `Gc::assume_init` unroots
+// them.
+impl<T> Finalize for MaybeUninit<T> {
+    #[inline]
+    fn has_finalizer(&self) -> bool {
+        false
+    }
+}
+unsafe impl<T> Trace for MaybeUninit<T> {
+    unsafe_empty_trace!();
 }
 
 impl<T> Finalize for [T] {}
@@ -266,6 +554,33 @@ unsafe impl<T: Trace> Trace for Vec<T> {
             mark(e);
         }
     });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        self.iter().any(Trace::needs_finalize)
+    }
+}
+
+// Only the elements which haven't been yielded yet are traced. The remaining
+// elements of a `vec_deque::IntoIter`, or of the `IntoIter`s of the maps and
+// sets, can't be accessed, so they don't implement `Trace`. Collect them into
+// a `Vec` to store them on the heap instead.
+impl<T> Finalize for std::vec::IntoIter<T> {}
+unsafe impl<T: Trace> Trace for std::vec::IntoIter<T> {
+    custom_trace!(this, {
+        for e in this.as_slice() {
+            mark(e);
+        }
+    });
+}
+
+impl<T, const N: usize> Finalize for std::array::IntoIter<T, N> {}
+unsafe impl<T: Trace, const N: usize> Trace for std::array::IntoIter<T, N> {
+    custom_trace!(this, {
+        for e in this.as_slice() {
+            mark(e);
+        }
+    });
 }
 
 impl<T> Finalize for Option<T> {}
@@ -275,6 +590,11 @@ unsafe impl<T: Trace> Trace for Option<T> {
             mark(v);
         }
     });
+
+    #[inline]
+    fn needs_finalize(&self) -> bool {
+        self.as_ref().is_some_and(Trace::needs_finalize)
+    }
 }
 
 impl<T, E> Finalize for Result<T, E> {}
@@ -315,10 +635,12 @@ unsafe impl<T: Trace> Trace for BTreeSet<T> {
     });
 }
 
+// The hasher of a `HashMap` or `HashSet` is never traced, so that maps can
+// use hashers which don't implement `Trace`. Hashers containing `Gc`s are
+// unsupported.
 impl<K, V, S> Finalize for HashMap<K, V, S> {}
-unsafe impl<K: Trace, V: Trace, S: Trace> Trace for HashMap<K, V, S> {
+unsafe impl<K: Trace, V: Trace, S> Trace for HashMap<K, V, S> {
     custom_trace!(this, {
-        mark(this.hasher());
         for (k, v) in this {
             mark(k);
             mark(v);
@@ -327,9 +649,8 @@ unsafe impl<K: Trace, V: Trace, S: Trace> Trace for HashMap<K, V, S> {
 }
 
 impl<T, S> Finalize for HashSet<T, S> {}
-unsafe impl<T: Trace, S: Trace> Trace for HashSet<T, S> {
+unsafe impl<T: Trace, S> Trace for HashSet<T, S> {
     custom_trace!(this, {
-        mark(this.hasher());
         for v in this {
             mark(v);
         }
@@ -345,11 +666,28 @@ unsafe impl<T: Trace> Trace for LinkedList<T> {
     });
 }
 
-impl<T: ?Sized> Finalize for PhantomData<T> {}
+impl<T: ?Sized> Finalize for PhantomData<T> {
+    #[inline]
+    fn has_finalizer(&self) -> bool {
+        false
+    }
+}
 unsafe impl<T: ?Sized> Trace for PhantomData<T> {
     unsafe_empty_trace!();
 }
 
+// A weak reference can't keep its target alive, and its target is rooted or
+// unrooted only through its strong references, so there is nothing to trace.
+impl<T: ?Sized> Finalize for rc::Weak<T> {}
+unsafe impl<T: ?Sized> Trace for rc::Weak<T> {
+    unsafe_empty_trace!();
+}
+
+impl<T: ?Sized> Finalize for sync::Weak<T> {}
+unsafe impl<T: ?Sized> Trace for sync::Weak<T> {
+    unsafe_empty_trace!();
+}
+
 impl<T> Finalize for VecDeque<T> {}
 unsafe impl<T: Trace> Trace for VecDeque<T> {
     custom_trace!(this, {
@@ -359,7 +697,9 @@ unsafe impl<T: Trace> Trace for VecDeque<T> {
     });
 }
 
-impl<'a, T: ToOwned + ?Sized> Finalize for Cow<'a, T>{}
+// This covers `Cow<'a, str>`, whose owned `String` has nothing to trace, and
+// `Cow<'a, [T]>` for `T: Trace + Clone`, so they need no impls of their own.
+impl<'a, T: ToOwned + ?Sized> Finalize for Cow<'a, T> {}
 unsafe impl<'a, T: ToOwned + ?Sized> Trace for Cow<'a, T>
 where
     T::Owned: Trace,
@@ -371,6 +711,70 @@ where
     });
 }
 
+impl<T> Finalize for Wrapping<T> {}
+unsafe impl<T: Trace> Trace for Wrapping<T> {
+    custom_trace!(this, {
+        mark(&this.0);
+    });
+}
+
+impl<T> Finalize for Reverse<T> {}
+unsafe impl<T: Trace> Trace for Reverse<T> {
+    custom_trace!(this, {
+        mark(&this.0);
+    });
+}
+
+// Tracing only reads the pointer, which doesn't move the pinned value.
+impl<P> Finalize for Pin<P> {}
+unsafe impl<P: Trace> Trace for Pin<P> {
+    custom_trace!(this, {
+        mark(pin_pointer(this));
+    });
+}
+
+fn pin_pointer<P>(pin: &Pin<P>) -> &P {
+    // `Pin` is `#[repr(transparent)]`.
+    unsafe { &*(pin as *const Pin<P>).cast::<P>() }
+}
+
+impl<T> Finalize for Range<T> {}
+unsafe impl<T: Trace> Trace for Range<T> {
+    custom_trace!(this, {
+        mark(&this.start);
+        mark(&this.end);
+    });
+}
+
+impl<T> Finalize for RangeFrom<T> {}
+unsafe impl<T: Trace> Trace for RangeFrom<T> {
+    custom_trace!(this, {
+        mark(&this.start);
+    });
+}
+
+impl<T> Finalize for RangeInclusive<T> {}
+unsafe impl<T: Trace> Trace for RangeInclusive<T> {
+    custom_trace!(this, {
+        mark(this.start());
+        mark(this.end());
+    });
+}
+
+impl<T> Finalize for RangeTo<T> {}
+unsafe impl<T: Trace> Trace for RangeTo<T> {
+    custom_trace!(this, {
+        mark(&this.end);
+    });
+}
+
+impl<T> Finalize for RangeToInclusive<T> {}
+unsafe impl<T: Trace> Trace for RangeToInclusive<T> {
+    custom_trace!(this, {
+        mark(&this.end);
+    });
+}
+
 impl<T> Finalize for BuildHasherDefault<T> {}
 unsafe impl<T> Trace for BuildHasherDefault<T> {
     unsafe_empty_trace!();
@@ -0,0 +1,41 @@
+use gc::{force_collect, set_allocator, Gc, GcAllocator};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Counts the live allocations made through it.
+struct Counting(Rc<Cell<isize>>);
+
+unsafe impl GcAllocator for Counting {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0.set(self.0.get() + 1);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn allocates_with_allocator() {
+    let live = Rc::new(Cell::new(0));
+    set_allocator(Counting(live.clone()));
+
+    let a = Gc::new(vec![Gc::new(1), Gc::new(2)]);
+    let b: Gc<[u8; 3]> = Gc::from(Box::new([1, 2, 3]));
+    assert_eq!(live.get(), 4);
+
+    drop(a);
+    force_collect();
+    assert_eq!(live.get(), 1);
+    assert_eq!(*b, [1, 2, 3]);
+}
+
+#[test]
+#[should_panic = "set_allocator called after allocating a Gc"]
+fn set_after_allocating() {
+    let _a = Gc::new(1);
+    set_allocator(Counting(Rc::default()));
+}
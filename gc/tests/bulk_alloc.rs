@@ -0,0 +1,33 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, with_bulk_alloc, Finalize, Gc, Trace};
+
+#[derive(Trace)]
+struct Garbage;
+
+impl Finalize for Garbage {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+#[test]
+fn bulk_alloc_defers_collection() {
+    force_collect();
+
+    let kept = with_bulk_alloc(|| {
+        let kept: Vec<_> = (0..1000).map(Gc::new).collect();
+        with_bulk_alloc(|| {
+            for _ in 0..1000 {
+                drop(Gc::new(Garbage));
+            }
+        });
+        assert_eq!(finalized(), 0);
+        kept
+    });
+
+    // A single collection runs once the outermost batch is done.
+    assert_eq!(finalized(), 1000);
+    assert_eq!(kept.iter().map(|k| **k).sum::<i32>(), 999 * 1000 / 2);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{finalized, reset_finalized};
+use gc::{collect_step, force_collect, Finalize, Gc, GcCell, Trace};
+use std::time::Duration;
+
+#[derive(Trace)]
+struct Node {
+    next: Option<Gc<Node>>,
+}
+
+impl Finalize for Node {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+fn list(len: usize) -> Gc<Node> {
+    let mut head = Gc::new(Node { next: None });
+    for _ in 1..len {
+        head = Gc::new(Node { next: Some(head) });
+    }
+    head
+}
+
+fn collect_in_steps() -> usize {
+    let mut steps = 1;
+    while !collect_step(Duration::ZERO) {
+        steps += 1;
+    }
+    steps
+}
+
+#[test]
+fn collects_in_steps() {
+    force_collect();
+    reset_finalized();
+
+    let live = list(20);
+    drop(list(10));
+
+    assert!(collect_in_steps() > 1);
+    assert_eq!(finalized(), 10);
+
+    drop(live);
+    collect_in_steps();
+    assert_eq!(finalized(), 30);
+}
+
+#[test]
+fn tracks_mutation_between_steps() {
+    let a = Gc::new(GcCell::new(Vec::new()));
+    let b = Gc::new(GcCell::new(vec![list(5)]));
+    force_collect();
+    reset_finalized();
+
+    // Keep moving the list between the two cells, so that it is regularly
+    // stored into a cell which has already been traced.
+    let mut from_a = false;
+    while !collect_step(Duration::ZERO) {
+        let (from, to) = if from_a { (&a, &b) } else { (&b, &a) };
+        let moved = from.borrow_mut().pop().unwrap();
+        to.borrow_mut().push(moved);
+        from_a = !from_a;
+    }
+
+    assert_eq!(finalized(), 0);
+    let cell = if from_a { &a } else { &b };
+    assert!(cell.borrow()[0].next.is_some());
+}
+
+#[test]
+fn force_collect_discards_progress() {
+    force_collect();
+    reset_finalized();
+
+    let live = list(10);
+    let dead = list(10);
+    assert!(!collect_step(Duration::ZERO));
+    drop(dead);
+    force_collect();
+    assert_eq!(finalized(), 10);
+
+    collect_in_steps();
+    assert_eq!(finalized(), 10);
+    drop(live);
+}
@@ -0,0 +1,121 @@
+//! Collections while a `GcCell` is mutably borrowed, which root its contents
+//! instead of tracing them.
+
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, Finalize, Gc, GcCell, GcCellRefMut, Trace};
+
+#[test]
+fn mapped_borrow_roots_whole_contents() {
+    let cell = Gc::new_cell((Gc::new(Counted(0)), Gc::new(Counted(0))));
+    {
+        let mut first = GcCellRefMut::map(cell.borrow_mut(), |p| &mut p.0);
+        *first = Gc::new(Counted(0));
+        force_collect();
+        // Only the replaced value is freed, and the unborrowed field is kept.
+        assert_eq!(finalized(), 1);
+    }
+    force_collect();
+    assert_eq!(finalized(), 1);
+
+    drop(cell);
+    force_collect();
+    assert_eq!(finalized(), 3);
+}
+
+#[test]
+fn mapped_borrow_of_plain_field_roots_gcs() {
+    let cell = Gc::new_cell((0u32, Gc::new(Counted(1))));
+    {
+        // The borrow is narrowed to a field without `Gc`s, which doesn't
+        // let the collector trace through the rest of the contents.
+        let mut count = GcCellRefMut::map(cell.borrow_mut(), |p| &mut p.0);
+        *count += 1;
+        force_collect();
+        assert_eq!(finalized(), 0);
+    }
+    force_collect();
+    assert_eq!(finalized(), 0);
+    assert_eq!(cell.borrow().1 .0, 1);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn unrooted_contents_are_caught() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let cell = Gc::new_cell(Gc::new(Counted(0)));
+    let guard = cell.borrow_mut();
+    // Clear the root bit set by `borrow_mut`, as if it had been forgotten.
+    unsafe { (*cell).unroot() };
+    let collected = catch_unwind(AssertUnwindSafe(force_collect));
+    unsafe { (*cell).root() };
+    drop(guard);
+
+    let message = *collected.unwrap_err().downcast::<&str>().unwrap();
+    assert_eq!(
+        message,
+        "traced a mutably borrowed GcCell whose contents aren't rooted"
+    );
+    force_collect();
+    assert_eq!(finalized(), 0);
+}
+
+#[test]
+fn contents_allocated_during_borrow() {
+    let cell = Gc::new_cell(Vec::new());
+    {
+        let mut v = cell.borrow_mut();
+        for _ in 0..10 {
+            v.push(Gc::new(Counted(0)));
+            force_collect();
+        }
+    }
+    force_collect();
+    assert_eq!(finalized(), 0);
+    cell.borrow_mut().clear();
+    force_collect();
+    assert_eq!(finalized(), 10);
+}
+
+#[derive(Trace, Finalize)]
+struct Node {
+    counted: Counted,
+    next: GcCell<Option<Gc<Node>>>,
+}
+
+#[test]
+fn unrooted_after_borrow() {
+    let node = Gc::new(Node {
+        counted: Counted(0),
+        next: GcCell::new(None),
+    });
+    {
+        let mut next = node.next.borrow_mut();
+        *next = Some(node.clone());
+        force_collect();
+    }
+    // The cycle is no longer rooted through the cell, so it's freed.
+    drop(node);
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+#[test]
+fn nested_borrows() {
+    let outer = Gc::new_cell(GcCell::new(Gc::new(Counted(0))));
+    {
+        let inner_cell = outer.borrow_mut();
+        let mut inner = inner_cell.borrow_mut();
+        *inner = Gc::new(Counted(0));
+        force_collect();
+        assert_eq!(finalized(), 1);
+    }
+    force_collect();
+    assert_eq!(finalized(), 1);
+
+    drop(outer);
+    force_collect();
+    assert_eq!(finalized(), 2);
+}
@@ -0,0 +1,75 @@
+#![cfg(all(feature = "unstable-config", feature = "unstable-stats"))]
+
+use gc::{configure, force_collect, reserve, stats, CollectionTrigger, Gc};
+
+fn collections_during(allocs: usize, size: usize) -> usize {
+    force_collect();
+    let before = stats().collections_performed;
+    for _ in 0..allocs {
+        drop(Gc::new(vec![0u8; size]));
+    }
+    stats().collections_performed - before
+}
+
+#[test]
+fn bytes_by_default() {
+    configure(|c| c.threshold = 1 << 20);
+    assert_eq!(collections_during(100, 8), 0);
+}
+
+#[test]
+fn allocation_count() {
+    configure(|c| {
+        c.threshold = 1 << 20;
+        c.trigger = CollectionTrigger::Allocations(10);
+    });
+    // The first allocation after every 10 collects.
+    assert_eq!(collections_during(100, 8), 9);
+}
+
+#[test]
+fn either() {
+    configure(|c| {
+        c.threshold = 1 << 20;
+        c.trigger = CollectionTrigger::Either(1000);
+    });
+    assert_eq!(collections_during(100, 8), 0);
+
+    configure(|c| c.threshold = 100);
+    assert!(collections_during(100, 8) > 0);
+}
+
+#[test]
+fn manual_only() {
+    configure(|c| {
+        c.threshold = 100;
+        c.auto_collect = false;
+    });
+    assert_eq!(collections_during(100, 8), 0);
+    assert!(stats().bytes_allocated > 100);
+
+    force_collect();
+    assert_eq!(stats().bytes_allocated, 0);
+}
+
+#[test]
+fn reserved_bytes() {
+    fn collections_after_reserving(reserved: usize) -> usize {
+        force_collect();
+        reserve(reserved);
+        let before = stats().collections_performed;
+        for _ in 0..100 {
+            drop(Gc::new(vec![0u8; 8]));
+        }
+        stats().collections_performed - before
+    }
+
+    configure(|c| c.threshold = 1000);
+    assert_eq!(collections_after_reserving(1 << 20), 0);
+    // The reservation ends once it's used up.
+    assert!(collections_after_reserving(1000) > 0);
+
+    // It's capped relative to the threshold.
+    configure(|c| c.threshold = 100);
+    assert!(collections_after_reserving(usize::MAX) > 0);
+}
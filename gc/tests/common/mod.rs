@@ -0,0 +1,33 @@
+//! Helpers shared by the integration tests, for counting finalizer runs.
+
+#![allow(dead_code)]
+
+use gc::{Finalize, Trace};
+use std::cell::Cell;
+
+thread_local!(static FINALIZED: Cell<usize> = const { Cell::new(0) });
+
+/// Counts a finalizer run, from the `Finalize` impl of a test's own type.
+pub fn count_finalized() {
+    FINALIZED.with(|f| f.set(f.get() + 1));
+}
+
+/// Returns how many finalizer runs were counted on this thread.
+pub fn finalized() -> usize {
+    FINALIZED.with(Cell::get)
+}
+
+/// Resets the count returned by `finalized`.
+pub fn reset_finalized() {
+    FINALIZED.with(|f| f.set(0));
+}
+
+/// A value whose finalizer runs are counted.
+#[derive(Trace, Clone, Debug, PartialEq)]
+pub struct Counted(pub u32);
+
+impl Finalize for Counted {
+    fn finalize(&self) {
+        count_finalized();
+    }
+}
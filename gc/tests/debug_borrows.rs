@@ -0,0 +1,38 @@
+#![cfg(feature = "debug-borrows")]
+
+use gc::{Gc, GcCell};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let err = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    match err.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(err) => err.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+#[test]
+fn names_the_mutable_borrow() {
+    let cell = Gc::new(GcCell::new(1));
+    let addr = format!("{:#x}", &*cell as *const GcCell<i32> as usize);
+
+    let outer = cell.borrow_mut();
+    let msg = panic_message(|| drop(cell.borrow()));
+    assert!(msg.starts_with("reentrant borrow of the same GcCell at "));
+    assert!(msg.contains(&addr));
+    assert!(msg.contains("already mutably borrowed at "));
+    assert!(msg.contains("debug_borrows.rs"));
+    let msg = panic_message(|| drop(cell.borrow_mut()));
+    assert!(msg.contains("debug_borrows.rs"));
+    drop(outer);
+
+    let outer = cell.borrow();
+    let msg = panic_message(|| drop(cell.borrow_mut()));
+    assert!(msg.contains(&addr));
+    assert!(msg.ends_with("GcCell<T> already borrowed"));
+    drop(outer);
+
+    // Ended borrows are forgotten.
+    *cell.borrow_mut() += 1;
+    assert_eq!(cell.get(), 2);
+}
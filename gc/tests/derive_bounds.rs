@@ -10,3 +10,47 @@ struct NotTrace;
 fn test_derive_bounds() {
     let _: Gc<Thunk<NotTrace>> = Gc::new(Thunk(|| NotTrace));
 }
+
+// References are not traced, so this only requires T: Trace for the Gc.
+#[derive(Finalize, Trace)]
+struct Borrowing<'a, T: Trace + 'static>(&'a T, Gc<T>);
+
+#[test]
+fn test_derive_lifetime() {
+    let local = 1;
+    let borrowing = Borrowing(&local, Gc::new(2));
+    let _: Gc<Borrowing<'static, i32>> = Gc::new(Borrowing(&3, borrowing.1.clone()));
+    assert_eq!((*borrowing.0, *borrowing.1), (1, 2));
+}
+
+// Only the projection needs to be Trace, not the iterator itself.
+#[derive(Finalize, Trace)]
+struct Peeked<I: Iterator>(Option<I::Item>);
+
+#[derive(Finalize, Trace)]
+struct PeekedWhere<I>
+where
+    I: Iterator,
+{
+    item: Option<I::Item>,
+}
+
+struct Untraced(std::vec::IntoIter<Gc<i32>>);
+
+impl Iterator for Untraced {
+    type Item = Gc<i32>;
+
+    fn next(&mut self) -> Option<Gc<i32>> {
+        self.0.next()
+    }
+}
+
+#[test]
+fn test_derive_associated_type() {
+    let mut iter = Untraced(vec![Gc::new(1)].into_iter());
+    let peeked: Gc<Peeked<Untraced>> = Gc::new(Peeked(iter.next()));
+    let peeked_where: Gc<PeekedWhere<Untraced>> = Gc::new(PeekedWhere { item: None });
+    gc::force_collect();
+    assert_eq!(peeked.0.as_deref(), Some(&1));
+    assert!(peeked_where.item.is_none());
+}
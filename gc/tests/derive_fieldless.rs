@@ -0,0 +1,58 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::cell::Cell;
+
+#[derive(Trace)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl Finalize for Color {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+#[derive(Trace, Finalize)]
+struct Unit;
+
+#[derive(Trace, Finalize)]
+enum Never {}
+
+#[derive(Trace, Finalize)]
+struct Ignored {
+    #[unsafe_ignore_trace]
+    _n: Cell<u32>,
+}
+
+#[test]
+fn finalize_glue_calls_finalize() {
+    let red = Color::Red;
+    red.finalize_glue();
+    assert_eq!(finalized(), 1);
+    assert!(red.needs_finalize());
+    // The derived `Drop` impl finalizes it again.
+    drop(red);
+    assert_eq!(finalized(), 2);
+
+    assert!(!Unit.needs_finalize());
+    assert!(!Ignored { _n: Cell::new(0) }.needs_finalize());
+}
+
+#[test]
+fn collected() {
+    let colors = Gc::new(vec![Gc::new(Color::Blue), Gc::new(Color::Green)]);
+    force_collect();
+    assert!(matches!(*colors[0], Color::Blue));
+    drop(colors);
+    force_collect();
+    assert_eq!(finalized(), 2);
+
+    let _unit = Gc::new(Unit);
+    let _never: Option<Gc<Never>> = None;
+    force_collect();
+}
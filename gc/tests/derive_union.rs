@@ -0,0 +1,50 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::cell::Cell;
+use std::mem::ManuallyDrop;
+
+/// A value which is always initialized as a `Gc`, but may also be read as a
+/// raw address.
+#[derive(Trace, Finalize)]
+#[unsafe_union_trace(gc)]
+union Slot {
+    gc: ManuallyDrop<Gc<Counted>>,
+    addr: usize,
+}
+
+#[test]
+fn traces_the_named_field() {
+    let slot = Gc::new(Slot {
+        gc: ManuallyDrop::new(Gc::new(Counted(0))),
+    });
+    assert_ne!(unsafe { slot.addr }, 0);
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    drop(slot);
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+thread_local!(static FINALIZED_BITS: Cell<u32> = const { Cell::new(0) });
+
+#[derive(Trace)]
+#[unsafe_union_trace(bits)]
+union Bits {
+    bits: u32,
+    float: f32,
+}
+
+impl Finalize for Bits {
+    fn finalize(&self) {
+        FINALIZED_BITS.with(|f| f.set(unsafe { self.bits }));
+    }
+}
+
+#[test]
+fn dropping_finalizes() {
+    drop(Bits { float: 1.0 });
+    assert_eq!(FINALIZED_BITS.with(Cell::get), 1.0f32.to_bits());
+}
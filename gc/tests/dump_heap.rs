@@ -0,0 +1,59 @@
+#![cfg(feature = "unstable-stats")]
+
+use gc::{dump_heap, Finalize, Gc, GcCell, GcPointer, HeapNode, Trace};
+
+#[derive(Trace, Finalize)]
+struct Node {
+    next: GcCell<Option<Gc<Node>>>,
+}
+
+fn node(next: Option<Gc<Node>>) -> Gc<Node> {
+    Gc::new(Node {
+        next: GcCell::new(next),
+    })
+}
+
+fn find(heap: &[HeapNode], gc: &Gc<Node>) -> HeapNode {
+    let ptr = GcPointer::from(gc);
+    heap.iter().find(|n| n.address == ptr).unwrap().clone()
+}
+
+#[test]
+fn dump_heap_graph() {
+    let leaf = node(None);
+    let root = node(Some(leaf.clone()));
+    drop(leaf);
+
+    let a = node(None);
+    let b = node(Some(a.clone()));
+    *a.next.borrow_mut() = Some(b.clone());
+    let (a_ptr, b_ptr) = (GcPointer::from(&a), GcPointer::from(&b));
+    let a_weak = a.clone();
+    drop((a, b));
+
+    let heap = dump_heap();
+    assert_eq!(heap.len(), 4);
+
+    let root_node = find(&heap, &root);
+    let leaf_ptr = GcPointer::from(root.next.borrow().as_ref().unwrap());
+    assert_eq!(root_node.roots, 1);
+    assert!(root_node.reachable);
+    assert_eq!(root_node.edges, vec![leaf_ptr]);
+    assert!(root_node.size > 0);
+
+    let leaf_node = heap.iter().find(|n| n.address == leaf_ptr).unwrap();
+    assert_eq!(leaf_node.roots, 0);
+    assert!(leaf_node.reachable);
+    assert!(leaf_node.edges.is_empty());
+
+    let a_node = find(&heap, &a_weak);
+    assert_eq!(a_node.edges, vec![b_ptr]);
+    drop(a_weak);
+
+    let heap = dump_heap();
+    let a_node = heap.iter().find(|n| n.address == a_ptr).unwrap();
+    let b_node = heap.iter().find(|n| n.address == b_ptr).unwrap();
+    assert!(!a_node.reachable);
+    assert!(!b_node.reachable);
+    assert_eq!(b_node.edges, vec![a_ptr]);
+}
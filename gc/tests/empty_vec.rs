@@ -0,0 +1,24 @@
+use gc::{force_collect, Gc};
+
+#[test]
+fn empty_vec_is_shared() {
+    let a: Gc<Vec<Gc<i32>>> = Gc::empty_vec();
+    let b: Gc<Vec<Gc<i32>>> = Gc::empty_vec();
+    assert!(a.is_empty());
+    assert!(Gc::ptr_eq(&a, &b));
+
+    drop((a, b));
+    force_collect();
+
+    let c: Gc<Vec<Gc<i32>>> = Gc::empty_vec();
+    let d: Gc<Vec<Gc<i32>>> = Gc::empty_vec();
+    assert!(c.is_empty());
+    assert!(Gc::ptr_eq(&c, &d));
+}
+
+#[test]
+fn empty_vec_per_type() {
+    let a: Gc<Vec<u8>> = Gc::empty_vec();
+    let b: Gc<Vec<u16>> = Gc::empty_vec();
+    assert_ne!(Gc::as_ptr(&a) as *const u8, Gc::as_ptr(&b) as *const u8);
+}
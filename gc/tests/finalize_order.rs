@@ -0,0 +1,41 @@
+use gc::{force_collect, Finalize, Gc, GcCell, Trace};
+use std::cell::RefCell;
+
+thread_local!(static ORDER: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) });
+
+#[derive(Trace)]
+struct Named {
+    #[unsafe_ignore_trace]
+    name: &'static str,
+    other: GcCell<Option<Gc<Named>>>,
+}
+
+impl Finalize for Named {
+    fn finalize(&self) {
+        // The other object is dying too, but hasn't been freed yet.
+        if let Some(other) = &*self.other.borrow() {
+            assert!(!other.name.is_empty());
+        }
+        ORDER.with(|order| order.borrow_mut().push(self.name));
+    }
+}
+
+fn named(name: &'static str) -> Gc<Named> {
+    Gc::new(Named {
+        name,
+        other: GcCell::new(None),
+    })
+}
+
+#[test]
+fn finalize_in_reverse_allocation_order() {
+    let first = named("first");
+    let second = named("second");
+    *first.other.borrow_mut() = Some(second.clone());
+    *second.other.borrow_mut() = Some(first.clone());
+    let third = named("third");
+    drop((first, second, third));
+
+    force_collect();
+    ORDER.with(|order| assert_eq!(*order.borrow(), ["third", "second", "first"]));
+}
@@ -0,0 +1,43 @@
+use gc::{finalizer_safe, force_collect, with_finalizer_access, Finalize, Gc, Trace};
+use std::cell::Cell;
+
+thread_local!(static SEEN: Cell<Option<(u32, bool, bool)>> = const { Cell::new(None) });
+
+#[derive(Trace, Finalize)]
+#[unsafe_no_drop_check]
+struct Reader {
+    // Kept reachable by the test.
+    target: Gc<u32>,
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        let before = finalizer_safe();
+        let value = unsafe { with_finalizer_access(|| *self.target) };
+        SEEN.with(|s| s.set(Some((value, before, finalizer_safe()))));
+    }
+}
+
+#[test]
+fn deref_reachable_while_dropping() {
+    let target = Gc::new(7);
+    drop(Gc::new(Reader {
+        target: target.clone(),
+    }));
+    force_collect();
+    // Access is only allowed within the call.
+    assert_eq!(SEEN.with(Cell::get), Some((7, false, false)));
+    assert!(finalizer_safe());
+}
+
+#[test]
+fn restored_after_panic() {
+    let result = std::panic::catch_unwind(|| unsafe {
+        with_finalizer_access(|| {
+            assert!(finalizer_safe());
+            panic!("in f");
+        })
+    });
+    assert!(result.is_err());
+    assert!(finalizer_safe());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, minor_collect, Finalize, Gc, Trace};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[derive(Trace)]
+struct Node {
+    next: Option<Gc<Node>>,
+    panics: bool,
+}
+
+impl Finalize for Node {
+    fn finalize(&self) {
+        common::count_finalized();
+        if self.panics {
+            panic!("finalizer panicked");
+        }
+    }
+}
+
+fn node(next: Option<Gc<Node>>, panics: bool) -> Gc<Node> {
+    Gc::new(Node { next, panics })
+}
+
+#[test]
+fn retry_after_panic() {
+    let kept = node(None, false);
+    drop(node(None, true));
+    drop(node(Some(kept.clone()), false));
+    assert!(catch_unwind(force_collect).is_err());
+    assert_eq!(finalized(), 2);
+
+    // Nothing was swept, and the retry frees both without finalizing them
+    // again.
+    force_collect();
+    assert_eq!(finalized(), 2);
+    assert!(!kept.panics);
+    drop(kept);
+    force_collect();
+    assert_eq!(finalized(), 3);
+}
+
+#[test]
+fn survivors_are_unmarked_after_panic() {
+    // The first minor collection is a full one.
+    minor_collect();
+    let parent = node(Some(node(None, false)), false);
+    drop(node(None, true));
+    assert!(catch_unwind(AssertUnwindSafe(minor_collect)).is_err());
+    assert_eq!(finalized(), 1);
+
+    // The survivors were left unmarked, so they're freed as soon as they're
+    // unreachable.
+    drop(parent);
+    force_collect();
+    assert_eq!(finalized(), 3);
+}
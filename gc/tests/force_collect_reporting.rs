@@ -0,0 +1,33 @@
+use gc::{force_collect_reporting, Finalize, Gc, GcCell, Trace};
+
+#[derive(Trace, Finalize)]
+struct Node {
+    next: GcCell<Option<Gc<Node>>>,
+}
+
+#[test]
+fn reports_freed_cycle() {
+    let a = Gc::new(Node {
+        next: GcCell::new(None),
+    });
+    let b = Gc::new(Node {
+        next: GcCell::new(Some(a.clone())),
+    });
+    *a.next.borrow_mut() = Some(b.clone());
+    let keep = Gc::new(Node {
+        next: GcCell::new(None),
+    });
+
+    let outcome = force_collect_reporting();
+    assert_eq!(outcome.objects_freed, 0);
+    assert_eq!(outcome.bytes_before, outcome.bytes_after);
+
+    drop((a, b));
+    let outcome = force_collect_reporting();
+    assert_eq!(outcome.objects_freed, 2);
+    assert!(outcome.bytes_after < outcome.bytes_before);
+
+    let outcome = force_collect_reporting();
+    assert_eq!(outcome.objects_freed, 0);
+    drop(keep);
+}
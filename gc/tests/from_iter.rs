@@ -0,0 +1,8 @@
+use gc::{force_collect, Gc};
+
+#[test]
+fn collect_gcs() {
+    let v: Gc<Vec<Gc<i32>>> = (0..20).map(Gc::new).collect();
+    force_collect();
+    assert_eq!(v.iter().map(|x| **x).sum::<i32>(), 190);
+}
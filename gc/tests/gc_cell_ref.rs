@@ -1,4 +1,4 @@
-use gc::{Gc, GcCell, GcCellRefMut};
+use gc::{force_collect, Gc, GcCell, GcCellRef, GcCellRefMut};
 
 #[test]
 fn test_gc_cell_ref_mut_map() {
@@ -6,3 +6,257 @@ fn test_gc_cell_ref_mut_map() {
     *GcCellRefMut::map(a.borrow_mut(), |(n, _)| n) = 2;
     assert_eq!(a.borrow_mut().0, 2);
 }
+
+#[test]
+fn test_gc_cell_ref_filter_map() {
+    let a = Gc::new(GcCell::new(vec![Gc::new(1), Gc::new(2)]));
+
+    let second = GcCellRef::filter_map(a.borrow(), |v| v.get(1))
+        .ok()
+        .unwrap();
+    assert_eq!(**second, 2);
+    assert!(a.try_borrow_mut().is_err());
+    drop(second);
+    assert!(a.try_borrow_mut().is_ok());
+
+    let orig = GcCellRef::filter_map(a.borrow(), |v| v.get(2))
+        .err()
+        .unwrap();
+    assert_eq!(orig.len(), 2);
+    assert!(a.try_borrow_mut().is_err());
+    drop(orig);
+    assert!(a.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn test_gc_cell_ref_mut_filter_map() {
+    let a = Gc::new(GcCell::new(vec![Gc::new(1), Gc::new(2)]));
+
+    let mut second = GcCellRefMut::filter_map(a.borrow_mut(), |v| v.get_mut(1))
+        .ok()
+        .unwrap();
+    *second = Gc::new(3);
+    assert!(a.try_borrow().is_err());
+    drop(second);
+    force_collect();
+    assert_eq!(*a.borrow()[1], 3);
+
+    let mut orig = GcCellRefMut::filter_map(a.borrow_mut(), |v| v.get_mut(2))
+        .err()
+        .unwrap();
+    assert_eq!(orig.len(), 2);
+    assert!(a.try_borrow().is_err());
+
+    // The returned guard still roots and unroots the contents exactly once.
+    orig.push(Gc::new(4));
+    force_collect();
+    drop(orig);
+    assert!(a.try_borrow_mut().is_ok());
+    force_collect();
+    assert_eq!(*a.borrow()[2], 4);
+}
+
+#[test]
+fn test_gc_cell_get_mut() {
+    let mut c = GcCell::new((0, Gc::new(1)));
+    c.get_mut().0 = 2;
+    assert!(c.try_borrow_mut().is_ok());
+
+    let a = Gc::new(c);
+    let r = a.borrow();
+    assert_eq!(r.0, 2);
+    assert!(a.try_borrow().is_ok());
+    assert!(a.try_borrow_mut().is_err());
+}
+
+#[test]
+fn test_gc_cell_ref_compare() {
+    let c = GcCell::new(5);
+    assert_eq!(c.borrow(), 5);
+    assert!(c.borrow() < 6);
+    assert!(c.borrow() >= 5);
+
+    let mut m = c.borrow_mut();
+    assert_eq!(m, 5);
+    *m = 7;
+    assert!(m > 6);
+    assert_ne!(m, 5);
+}
+
+#[test]
+fn test_gc_cell_ref_compare_unsized() {
+    let c = GcCell::new(String::from("abc"));
+    assert_eq!(c.borrow(), *"abc");
+    assert_eq!(c.borrow_mut(), *"abc");
+}
+
+#[test]
+fn tracked_borrow_marks_dirty() {
+    let c = Gc::new(GcCell::new((1, Gc::new(2))));
+    assert!(!c.take_dirty());
+
+    // Untracked borrows don't count.
+    c.borrow_mut().0 = 3;
+    assert!(!c.take_dirty());
+
+    {
+        let b = c.borrow_mut_tracked();
+        let mut b = GcCellRefMut::map(b, |t| &mut t.1);
+        *b = Gc::new(4);
+        // Only set once the borrow ends.
+        assert!(!c.take_dirty());
+    }
+    force_collect();
+    assert!(c.take_dirty());
+    assert!(!c.take_dirty());
+    assert_eq!(*c.borrow().1, 4);
+
+    // Reads keep working alongside the flag.
+    drop(c.borrow_mut_tracked());
+    let (r1, r2) = (c.borrow(), c.borrow());
+    assert_eq!(r1.0, r2.0);
+    drop((r1, r2));
+    assert!(c.take_dirty());
+}
+
+#[test]
+fn test_gc_cell_snapshots() {
+    let a = Gc::new(GcCell::new(vec![Gc::new(1), Gc::new(2)]));
+
+    let snapshot = a.borrow_cloned();
+    let first = GcCellRef::into_owned(GcCellRef::map(a.borrow(), |v| &v[0]));
+    assert!(a.try_borrow_mut().is_ok());
+
+    // The clones are roots, and keep the values alive on their own.
+    a.borrow_mut().clear();
+    force_collect();
+    assert_eq!(*snapshot[1], 2);
+    assert_eq!(*first, 1);
+}
+
+#[test]
+fn test_gc_cell_borrow_index() {
+    let a = Gc::new(GcCell::new(vec![Gc::new(1), Gc::new(2)]));
+
+    let second = a.borrow_index(1);
+    assert_eq!(**second, 2);
+    assert!(a.try_borrow_mut().is_err());
+    drop(second);
+
+    a.borrow_mut().push(Gc::new(3));
+    force_collect();
+    assert_eq!(**a.borrow_index(2), 3);
+    assert_eq!(*a.borrow_index(1..), [Gc::new(2), Gc::new(3)]);
+
+    // Indexing out of bounds releases the borrow.
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        a.borrow_index(3);
+    }));
+    assert!(result.is_err());
+    assert!(a.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn test_gc_index() {
+    let v = Gc::new(vec![1, 2, 3]);
+    assert_eq!(v[0], 1);
+    assert_eq!(v[1..], [2, 3]);
+}
+
+#[test]
+fn borrow_unguarded() {
+    let cell = GcCell::new(vec![1, 2]);
+    {
+        let _writing = cell.borrow_mut();
+        assert!(unsafe { cell.try_borrow_unguarded() }.is_err());
+    }
+
+    let values = unsafe { cell.try_borrow_unguarded() }.unwrap();
+    assert_eq!(*values, [1, 2]);
+    // The unguarded reference doesn't count as a borrow, so the cell can be
+    // mutably borrowed once it's no longer used.
+    cell.borrow_mut().push(3);
+}
+
+#[test]
+fn get_copies_value() {
+    let cell = GcCell::new(1);
+    let _reading = cell.borrow();
+    assert_eq!(cell.get(), 1);
+}
+
+#[test]
+#[should_panic(expected = "already mutably borrowed")]
+fn get_while_mutably_borrowed() {
+    let cell = GcCell::new(1);
+    let _writing = cell.borrow_mut();
+    cell.get();
+}
+
+#[test]
+fn test_gc_cell_ref_as_ref() {
+    use std::borrow::Borrow;
+    use std::path::Path;
+
+    fn len(s: impl AsRef<str>) -> usize {
+        s.as_ref().len()
+    }
+
+    fn file_name(p: impl AsRef<Path>) -> String {
+        p.as_ref().file_name().unwrap().to_str().unwrap().to_owned()
+    }
+
+    let cell = GcCell::new(String::from("dir/file"));
+    assert_eq!(len(cell.borrow()), 8);
+    assert_eq!(file_name(cell.borrow()), "file");
+    let r = cell.borrow();
+    let s: &String = Borrow::borrow(&r);
+    assert_eq!(s.len(), 8);
+    drop(r);
+
+    let mut r = cell.borrow_mut();
+    r.push('s');
+    let s: &String = Borrow::borrow(&r);
+    assert_eq!(s, "dir/files");
+    assert_eq!(len(r), 9);
+}
+
+#[test]
+fn derive_hash_with_cell() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    #[derive(PartialEq, Eq, Hash)]
+    struct Key {
+        name: &'static str,
+        count: GcCell<u32>,
+    }
+
+    let hash = |count| {
+        let mut hasher = DefaultHasher::new();
+        Key {
+            name: "a",
+            count: GcCell::new(count),
+        }
+        .hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(hash(1), hash(1));
+    assert_ne!(hash(1), hash(2));
+
+    // Hashing only takes a shared borrow.
+    let cell = GcCell::new(1);
+    let _reading = cell.borrow();
+    let mut hasher = DefaultHasher::new();
+    cell.hash(&mut hasher);
+}
+
+#[test]
+#[should_panic(expected = "hashed a GcCell while it is mutably borrowed")]
+fn hash_while_mutably_borrowed() {
+    use std::hash::Hash;
+
+    let cell = GcCell::new(1);
+    let _writing = cell.borrow_mut();
+    cell.hash(&mut std::collections::hash_map::DefaultHasher::new());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, Finalize, Gc, GcCell, GcCow, Trace};
+
+#[derive(Trace, Finalize)]
+struct Cache {
+    entry: GcCell<Option<GcCow<'static, Counted>>>,
+}
+
+static BORROWED: u32 = 7;
+
+#[test]
+fn owned_is_traced() {
+    let cache = Gc::new(Cache {
+        entry: GcCell::new(None),
+    });
+    *cache.entry.borrow_mut() = Some(GcCow::Owned(Gc::new(Counted(1))));
+    force_collect();
+    assert_eq!(finalized(), 0);
+    assert_eq!(cache.entry.borrow().as_ref().unwrap().0, 1);
+
+    *cache.entry.borrow_mut() = None;
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+#[test]
+fn into_owned() {
+    let borrowed = GcCow::from(&BORROWED);
+    assert!(borrowed.is_borrowed());
+    let owned = borrowed.into_owned();
+    assert_eq!(*owned, 7);
+
+    let cow = GcCow::from(owned.clone());
+    assert!(cow.is_owned());
+    let copy = cow.clone();
+    assert!(Gc::ptr_eq(&cow.into_owned(), &owned));
+    assert_eq!(copy, GcCow::Borrowed(&7));
+    assert_eq!(format!("{:?} {}", copy, copy), "7 7");
+}
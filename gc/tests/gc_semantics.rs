@@ -240,6 +240,42 @@ fn gccell_rooting() {
     FLAGS.with(|f| assert_eq!(f.get(), GcWatchFlags::new(3, 1, 2, 1, 1)));
 }
 
+#[test]
+fn clone_from() {
+    thread_local!(static FLAGS1: Cell<GcWatchFlags> = GcWatchFlags::zero());
+    thread_local!(static FLAGS2: Cell<GcWatchFlags> = GcWatchFlags::zero());
+
+    let x = Gc::new(GcWatch(&FLAGS1));
+    let y = Gc::new(GcWatch(&FLAGS2));
+
+    // Cloning onto a `Gc` to the same allocation leaves it alone
+    let mut a = x.clone();
+    a.clone_from(&x);
+    assert!(Gc::ptr_eq(&a, &x));
+    a.clone_from(&y);
+    assert!(Gc::ptr_eq(&a, &y));
+    FLAGS1.with(|f| assert_eq!(f.get(), GcWatchFlags::new(0, 0, 1, 0, 0)));
+    FLAGS2.with(|f| assert_eq!(f.get(), GcWatchFlags::new(0, 0, 1, 0, 0)));
+
+    // `a` no longer keeps `x` alive
+    drop(x);
+    force_collect();
+    FLAGS1.with(|f| assert_eq!(f.get(), GcWatchFlags::new(0, 0, 1, 1, 1)));
+
+    // Cloning into a `GcCell` leaves the cell's contents unrooted
+    let z = Gc::new(GcWatch(&FLAGS1));
+    let cell = Gc::new(GcCell::new(a));
+    cell.borrow_mut().clone_from(&z);
+    drop((y, z));
+    force_collect();
+    FLAGS2.with(|f| assert_eq!(f.get().finalize, 1));
+    FLAGS1.with(|f| assert_eq!(f.get().finalize, 1));
+
+    drop(cell);
+    force_collect();
+    FLAGS1.with(|f| assert_eq!(f.get().finalize, 2));
+}
+
 #[cfg(feature = "nightly")]
 // XXX: CoerceUnsize is unstable only
 #[test]
@@ -317,3 +353,146 @@ fn as_ptr() {
     assert_ne!(Gc::as_ptr(&b.0), Gc::as_ptr(&b2.0));
     assert_ne!(Gc::as_ptr(&b.0), a2_ptr);
 }
+
+#[test]
+fn debug_cycle() {
+    #[derive(Trace, Finalize, Debug)]
+    struct Node {
+        value: u32,
+        next: GcCell<Option<Gc<Node>>>,
+    }
+
+    let leaf = Gc::new(Node {
+        value: 2,
+        next: GcCell::new(None),
+    });
+    let node = Gc::new(Node {
+        value: 1,
+        next: GcCell::new(None),
+    });
+
+    // Shared, but acyclic, nodes are printed in full.
+    let shared = Gc::new((leaf.clone(), leaf.clone()));
+    assert_eq!(format!("{:?}", shared).matches("value: 2").count(), 2);
+
+    *node.next.borrow_mut() = Some(node.clone());
+    assert_eq!(
+        format!("{:?}", node),
+        "Node { value: 1, next: GcCell { value: Some(...) } }"
+    );
+}
+
+#[test]
+fn eq_cycle() {
+    #[derive(Trace, Finalize, PartialEq)]
+    struct Node {
+        value: u32,
+        next: GcCell<Option<Gc<Node>>>,
+    }
+
+    fn cycle(values: &[u32]) -> Gc<Node> {
+        let nodes: Vec<_> = values
+            .iter()
+            .map(|&value| {
+                Gc::new(Node {
+                    value,
+                    next: GcCell::new(None),
+                })
+            })
+            .collect();
+        for (i, node) in nodes.iter().enumerate() {
+            *node.next.borrow_mut() = Some(nodes[(i + 1) % nodes.len()].clone());
+        }
+        nodes[0].clone()
+    }
+
+    let a = cycle(&[1]);
+    assert!(a == cycle(&[1]));
+    assert!(a != cycle(&[2]));
+    // Unrolling a cycle doesn't change its values.
+    assert!(a == cycle(&[1, 1]));
+    assert!(cycle(&[1, 2]) != cycle(&[1, 3]));
+
+    // Cycles are only looked for in deeply nested comparisons.
+    let mut long: Vec<u32> = (0..200).collect();
+    assert!(cycle(&long) == cycle(&long));
+    long[150] = 0;
+    assert!(cycle(&long) != cycle(&(0..200).collect::<Vec<_>>()));
+
+    assert!(Gc::value_eq(&a, &cycle(&[1])));
+    assert!(Gc::ref_eq(&a, &a.clone()));
+    assert!(!Gc::ref_eq(&a, &cycle(&[1])));
+}
+
+#[test]
+fn new_cell() {
+    thread_local!(static TWO_STEP: Cell<GcWatchFlags> = GcWatchFlags::zero());
+    thread_local!(static NEW_CELL: Cell<GcWatchFlags> = GcWatchFlags::zero());
+    thread_local!(static BOXED: Cell<GcWatchFlags> = GcWatchFlags::zero());
+
+    let two_step = Gc::new(GcCell::new(GcWatch(&TWO_STEP)));
+    let new_cell = Gc::new_cell(GcWatch(&NEW_CELL));
+    let boxed = GcCell::boxed(GcWatch(&BOXED));
+    force_collect();
+    for flags in [&TWO_STEP, &NEW_CELL, &BOXED] {
+        flags.with(|f| assert_eq!(f.get(), GcWatchFlags::new(1, 0, 1, 0, 0)));
+    }
+
+    drop(two_step.borrow_mut());
+    drop(new_cell.borrow_mut());
+    drop(boxed.borrow_mut());
+    for flags in [&TWO_STEP, &NEW_CELL, &BOXED] {
+        flags.with(|f| assert_eq!(f.get(), GcWatchFlags::new(1, 1, 2, 0, 0)));
+    }
+}
+
+#[test]
+fn update_cell() {
+    thread_local!(static OLD: Cell<GcWatchFlags> = GcWatchFlags::zero());
+    thread_local!(static NEW: Cell<GcWatchFlags> = GcWatchFlags::zero());
+
+    let cell = Gc::new_cell(vec![Gc::new(GcWatch(&OLD))]);
+    let new = Gc::new(GcWatch(&NEW));
+    cell.update(|v| {
+        v.clear();
+        v.push(new);
+    });
+    assert_eq!(cell.borrow().len(), 1);
+    force_collect();
+    OLD.with(|f| assert_eq!(f.get(), GcWatchFlags::new(0, 0, 1, 1, 1)));
+    // Marked again after `OLD` was finalized.
+    NEW.with(|f| assert_eq!(f.get(), GcWatchFlags::new(2, 0, 1, 0, 0)));
+
+    drop(cell);
+    force_collect();
+    NEW.with(|f| assert_eq!(f.get(), GcWatchFlags::new(2, 0, 1, 1, 1)));
+}
+
+#[test]
+fn project() {
+    thread_local!(static FLAGS: Cell<GcWatchFlags> = GcWatchFlags::zero());
+
+    #[derive(Trace, Finalize)]
+    enum Value {
+        Int(u32),
+        Watched(Gc<GcWatch>),
+    }
+
+    let outer = Gc::new(vec![
+        Value::Int(0),
+        Value::Watched(Gc::new(GcWatch(&FLAGS))),
+    ]);
+    let watched = Gc::project(&outer, |v| match &v[1] {
+        Value::Watched(w) => w,
+        Value::Int(_) => unreachable!(),
+    });
+    assert!(matches!(&outer[1], Value::Watched(w) if Gc::ptr_eq(w, &watched)));
+
+    drop(outer);
+    force_collect();
+    FLAGS.with(|f| assert_eq!(f.get().finalize, 0));
+
+    drop(watched);
+    force_collect();
+    FLAGS.with(|f| assert_eq!(f.get().finalize, 1));
+}
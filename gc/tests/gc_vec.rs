@@ -0,0 +1,61 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, Finalize, Gc, GcVec, Trace};
+
+#[test]
+fn pushed_gcs_stay_alive() {
+    let vec = GcVec::new();
+    vec.push(Gc::new(Counted(1)));
+    vec.push(Gc::new(Counted(2)));
+    force_collect();
+    assert_eq!(finalized(), 0);
+    assert_eq!(vec.get(1).unwrap().0, 2);
+
+    drop(vec.pop());
+    force_collect();
+    assert_eq!(finalized(), 1);
+    assert_eq!(vec.len(), 1);
+
+    drop(vec);
+    force_collect();
+    assert_eq!(finalized(), 2);
+}
+
+#[test]
+fn traced_through_the_heap() {
+    #[derive(Trace, Finalize)]
+    struct Frame {
+        locals: GcVec<Gc<Counted>>,
+    }
+
+    let frame = Gc::new(Frame {
+        locals: GcVec::new(),
+    });
+    frame.locals.push(Gc::new(Counted(1)));
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    // A clone refers to the same vector.
+    let locals = frame.locals.clone();
+    drop(frame);
+    locals.push(Gc::new(Counted(2)));
+    force_collect();
+    assert_eq!(finalized(), 0);
+    assert_eq!(locals.iter().map(|c| c.0).collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn iterate_while_pushing() {
+    let vec: GcVec<u32> = (1..=3).collect();
+    let mut seen = Vec::new();
+    for v in &vec {
+        if v == 1 {
+            vec.push(4);
+        }
+        seen.push(v);
+    }
+    assert_eq!(seen, [1, 2, 3, 4]);
+    assert!(!vec.is_empty());
+    assert_eq!(*vec.borrow(), [1, 2, 3, 4]);
+}
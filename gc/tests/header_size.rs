@@ -0,0 +1,53 @@
+#![cfg(all(feature = "unstable-stats", target_pointer_width = "64"))]
+
+use gc::{force_collect, stats, with_bulk_alloc, Gc};
+
+fn bytes_per_gc<T: gc::Trace + 'static>(make: impl Fn() -> T) -> usize {
+    force_collect();
+    let before = stats().bytes_allocated;
+    let all = with_bulk_alloc(|| (0..1000).map(|_| Gc::new(make())).collect::<Vec<_>>());
+    let bytes = stats().bytes_allocated - before;
+    drop(all);
+    bytes / 1000
+}
+
+#[cfg(feature = "compact-header")]
+#[test]
+fn small_values_fit_after_header() {
+    // The header takes 20 bytes, and values aligned to at most 4 bytes
+    // don't need padding after it.
+    assert_eq!(bytes_per_gc(|| 0u8), 24);
+    assert_eq!(bytes_per_gc(|| 0u32), 24);
+    assert_eq!(bytes_per_gc(|| 0u64), 32);
+}
+
+#[cfg(not(feature = "compact-header"))]
+#[test]
+fn small_values_fit_after_header() {
+    // The header takes 24 bytes, and values are padded to its alignment.
+    assert_eq!(bytes_per_gc(|| 0u8), 32);
+    assert_eq!(bytes_per_gc(|| 0u32), 32);
+    assert_eq!(bytes_per_gc(|| 0u64), 32);
+}
+
+#[test]
+fn allocated_size_matches_bytes_allocated() {
+    force_collect();
+    let before = stats().bytes_allocated;
+    let small = Gc::new(0u8);
+    let vec = Gc::new(vec![0u64; 10]);
+    let dst = Gc::new_dst_with(0u64, &[0u16; 7]);
+    assert_eq!(
+        Gc::allocated_size(&small),
+        if cfg!(feature = "compact-header") {
+            24
+        } else {
+            32
+        }
+    );
+    assert_eq!(Gc::allocated_size(&vec), 48);
+    assert_eq!(
+        stats().bytes_allocated - before,
+        Gc::allocated_size(&small) + Gc::allocated_size(&vec) + Gc::allocated_size(&dst)
+    );
+}
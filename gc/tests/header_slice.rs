@@ -0,0 +1,120 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, Finalize, Gc, GcCell, HeaderSlice, Trace};
+use std::rc::Rc;
+
+#[derive(Trace, Clone)]
+struct Counted(u8);
+
+impl Finalize for Counted {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+#[test]
+fn traces_header_and_slice() {
+    let elems: Vec<_> = (0..4).map(|i| Gc::new(Counted(i))).collect();
+    let obj = Gc::new_dst_with(Gc::new(Counted(9)), &elems);
+    drop(elems);
+    force_collect();
+    assert_eq!(finalized(), 0);
+    assert_eq!(obj.header.0, 9);
+    let values: Vec<u8> = obj.slice.iter().map(|e| e.0).collect();
+    assert_eq!(values, [0, 1, 2, 3]);
+
+    drop(obj);
+    force_collect();
+    assert_eq!(finalized(), 5);
+}
+
+#[derive(Trace, Finalize, Clone)]
+struct Dropped {
+    #[unsafe_ignore_trace]
+    _rc: Rc<()>,
+}
+
+#[test]
+fn drops_header_and_slice() {
+    let dropped = Rc::new(());
+    let obj = Gc::new_dst_with(
+        Counted(0),
+        &[
+            Dropped {
+                _rc: Rc::clone(&dropped),
+            },
+            Dropped {
+                _rc: Rc::clone(&dropped),
+            },
+        ],
+    );
+    assert_eq!(Rc::strong_count(&dropped), 3);
+    drop(obj);
+    force_collect();
+    assert_eq!(Rc::strong_count(&dropped), 1);
+    assert_eq!(finalized(), 1);
+}
+
+#[test]
+fn empty_and_zero_sized() {
+    let empty: Gc<HeaderSlice<u64, u8>> = Gc::new_dst_with(1, &[]);
+    let units: Gc<HeaderSlice<(), ()>> = Gc::new_dst_with((), &[(); 5]);
+    force_collect();
+    assert_eq!(empty.header, 1);
+    assert!(empty.slice.is_empty());
+    assert_eq!(units.slice.len(), 5);
+}
+
+#[repr(align(32))]
+#[derive(Trace, Finalize, Clone, Debug, PartialEq)]
+struct Aligned(u8);
+
+#[test]
+fn aligned_parts() {
+    let a = Gc::new_dst_with(Aligned(1), &[Aligned(2), Aligned(3)]);
+    let b = Gc::new_dst_with(3u8, &[Aligned(4)]);
+    force_collect();
+    assert_eq!(Gc::as_ptr(&a) as *const u8 as usize % 32, 0);
+    assert_eq!(a.slice, [Aligned(2), Aligned(3)]);
+    assert_eq!(b.slice, [Aligned(4)]);
+    assert_eq!(
+        format!("{:?}", &*b),
+        "HeaderSlice { header: 3, slice: [Aligned(4)] }"
+    );
+}
+
+#[derive(Trace, Finalize)]
+struct Link {
+    counted: Counted,
+    next: GcCell<Option<Gc<HeaderSlice<Link, u8>>>>,
+}
+
+#[test]
+fn cycle_through_header() {
+    let obj = Gc::new_dst_with(
+        Link {
+            counted: Counted(0),
+            next: GcCell::new(None),
+        },
+        b"text",
+    );
+    *obj.header.next.borrow_mut() = Some(obj.clone());
+    force_collect();
+    assert_eq!(finalized(), 0);
+    drop(obj);
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+#[cfg(feature = "unstable-stats")]
+#[test]
+fn counts_whole_allocation() {
+    force_collect();
+    let before = gc::stats().bytes_allocated;
+    let obj = Gc::new_dst_with(0u64, &[0u64; 100]);
+    assert!(gc::stats().bytes_allocated - before >= 808);
+    drop(obj);
+    force_collect();
+    assert_eq!(gc::stats().bytes_allocated, before);
+}
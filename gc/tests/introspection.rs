@@ -0,0 +1,35 @@
+#![cfg(feature = "unstable-stats")]
+
+use gc::{collect_step, force_collect, Gc, GcCell};
+use std::time::Duration;
+
+#[test]
+fn root_count() {
+    let x = Gc::new(1);
+    assert_eq!(Gc::root_count(&x), 1);
+    let y = x.clone();
+    assert_eq!(Gc::root_count(&x), 2);
+
+    let holder = Gc::new(GcCell::new(None));
+    *holder.borrow_mut() = Some(y);
+    assert_eq!(Gc::root_count(&x), 1);
+    assert_eq!(Gc::root_count(holder.borrow().as_ref().unwrap()), 1);
+
+    drop(x);
+    assert_eq!(Gc::root_count(holder.borrow().as_ref().unwrap()), 0);
+}
+
+#[test]
+fn is_marked() {
+    let x = Gc::new(1);
+    assert!(!Gc::is_marked(&x));
+    force_collect();
+    assert!(!Gc::is_marked(&x));
+
+    let _garbage = Gc::new(0);
+    // The first step marks the roots.
+    assert!(!collect_step(Duration::from_secs(0)));
+    assert!(Gc::is_marked(&x));
+    while !collect_step(Duration::from_secs(1)) {}
+    assert!(!Gc::is_marked(&x));
+}
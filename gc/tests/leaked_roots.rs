@@ -0,0 +1,26 @@
+#![cfg(all(feature = "unstable-stats", debug_assertions))]
+
+use gc::{force_collect, stats, Gc, LEAK_COLLECTIONS};
+use std::mem;
+
+#[test]
+fn counts_leaked_roots() {
+    let held = Gc::new(1);
+    mem::forget(Gc::new(2));
+    let inner = Gc::new(vec![Gc::new(3)]);
+
+    force_collect();
+    assert_eq!(stats().rooted_objects, 3);
+    assert_eq!(stats().leaked_roots, 0);
+
+    for _ in 1..LEAK_COLLECTIONS {
+        force_collect();
+    }
+    assert_eq!(stats().leaked_roots, 3);
+
+    // Dropped roots stop counting.
+    drop((held, inner));
+    force_collect();
+    assert_eq!(stats().rooted_objects, 1);
+    assert_eq!(stats().leaked_roots, 1);
+}
@@ -0,0 +1,98 @@
+use gc::{force_collect, Finalize, Gc, GcCell, Trace};
+use std::cell::{Cell, RefCell};
+
+#[derive(Trace, Finalize, Clone)]
+struct Node {
+    value: i32,
+    child: Option<Gc<Node>>,
+}
+
+fn leaf(value: i32) -> Gc<Node> {
+    Gc::new(Node { value, child: None })
+}
+
+#[test]
+fn mutates_unique_in_place() {
+    let mut a = leaf(1);
+    let ptr = Gc::as_ptr(&a);
+    Gc::make_mut_scanning(&mut a).value = 2;
+    assert_eq!(Gc::as_ptr(&a), ptr);
+    assert_eq!(a.value, 2);
+}
+
+#[test]
+fn clones_when_shared_by_a_root() {
+    let mut a = leaf(1);
+    let b = a.clone();
+    Gc::make_mut_scanning(&mut a).value = 2;
+    assert!(!Gc::ptr_eq(&a, &b));
+    assert_eq!((a.value, b.value), (2, 1));
+}
+
+#[test]
+fn clones_when_shared_by_the_heap() {
+    let mut a = leaf(1);
+    let parent = Gc::new(Node {
+        value: 0,
+        child: Some(a.clone()),
+    });
+    Gc::make_mut_scanning(&mut a).value = 2;
+    assert!(!Gc::ptr_eq(&a, parent.child.as_ref().unwrap()));
+    assert_eq!(parent.child.as_ref().unwrap().value, 1);
+
+    // Shared through a `GcCell` too.
+    let mut b = leaf(1);
+    let cell = Gc::new(GcCell::new(vec![b.clone()]));
+    Gc::make_mut_scanning(&mut b).value = 2;
+    assert_eq!(cell.borrow()[0].value, 1);
+}
+
+#[test]
+fn children_stay_alive() {
+    let mut a = Gc::new(Node {
+        value: 0,
+        child: Some(leaf(1)),
+    });
+    {
+        let mut node = Gc::make_mut_scanning(&mut a);
+        let child = node.child.take().unwrap();
+        force_collect();
+        node.child = Some(Gc::new(Node {
+            value: 2,
+            child: Some(child),
+        }));
+        force_collect();
+    }
+    force_collect();
+
+    let child = a.child.as_ref().unwrap();
+    assert_eq!(child.value, 2);
+    assert_eq!(child.child.as_ref().unwrap().value, 1);
+}
+
+thread_local!(static SHARED: RefCell<Option<Gc<i32>>> = const { RefCell::new(None) });
+thread_local!(static FOUND_UNIQUE: Cell<Option<bool>> = const { Cell::new(None) });
+
+#[derive(Trace)]
+struct Scanner;
+
+impl Finalize for Scanner {
+    fn finalize(&self) {
+        SHARED.with(|shared| {
+            let mut shared = shared.borrow_mut();
+            let unique = Gc::get_mut_scanning(shared.as_mut().unwrap()).is_some();
+            FOUND_UNIQUE.with(|found| found.set(Some(unique)));
+        });
+    }
+}
+
+#[test]
+fn reports_shared_during_a_collection() {
+    SHARED.with(|shared| *shared.borrow_mut() = Some(Gc::new(1)));
+    drop(Gc::new(Scanner));
+    force_collect();
+
+    // The heap can't be scanned from a finalizer, so uniqueness can't be
+    // proven.
+    assert_eq!(FOUND_UNIQUE.with(Cell::get), Some(false));
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "unstable-stats")]
+
+use gc::{force_collect, mark_only, stats, Finalize, Gc, GcCell, GcPointer, Trace};
+
+#[derive(Trace, Finalize)]
+struct Node {
+    next: GcCell<Option<Gc<Node>>>,
+}
+
+fn node(next: Option<Gc<Node>>) -> Gc<Node> {
+    Gc::new(Node {
+        next: GcCell::new(next),
+    })
+}
+
+#[test]
+fn marks_without_sweeping() {
+    let leaf = node(None);
+    let root = node(Some(leaf.clone()));
+    let leaf_ptr = GcPointer::from(&leaf);
+    drop(leaf);
+
+    let a = node(None);
+    let b = node(Some(a.clone()));
+    *a.next.borrow_mut() = Some(b.clone());
+    let (a_ptr, b_ptr) = (GcPointer::from(&a), GcPointer::from(&b));
+    drop((a, b));
+
+    let bytes = stats().bytes_allocated;
+    let result = mark_only();
+    assert_eq!(result.reachable.len(), 2);
+    assert!(result.reachable.contains(&GcPointer::from(&root)));
+    assert!(result.reachable.contains(&leaf_ptr));
+    assert_eq!(result.unreachable.len(), 2);
+    assert!(result.unreachable.contains(&a_ptr));
+    assert!(result.unreachable.contains(&b_ptr));
+    assert_eq!(stats().bytes_allocated, bytes);
+
+    // Nothing is left marked, so a collection still frees the cycle.
+    assert_eq!(mark_only().unreachable.len(), 2);
+    force_collect();
+    assert!(mark_only().unreachable.is_empty());
+    assert!(root.next.borrow().is_some());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, minor_collect, Finalize, Gc, GcCell, GcOnceCell, Trace};
+use std::cell::RefCell;
+
+#[test]
+fn frees_only_young() {
+    let old = Gc::new(Counted(1));
+    // The first minor collection is a full one.
+    assert!(minor_collect());
+    assert_eq!(finalized(), 0);
+
+    drop(old);
+    drop(Gc::new(Counted(2)));
+    let young = Gc::new(Counted(3));
+    minor_collect();
+    assert_eq!(finalized(), 1);
+
+    // Survivors are promoted.
+    drop(young);
+    minor_collect();
+    assert_eq!(finalized(), 1);
+
+    force_collect();
+    assert_eq!(finalized(), 3);
+}
+
+#[test]
+fn keeps_young_reachable_from_old() {
+    let cell = Gc::new(GcCell::new(None));
+    let once = Gc::new(GcOnceCell::new());
+    let mut unique = Gc::new(None);
+    minor_collect();
+
+    *cell.borrow_mut() = Some(Gc::new(Counted(1)));
+    once.set(Gc::new(Counted(2))).ok().unwrap();
+    *Gc::get_mut_scanning(&mut unique).unwrap() = Some(Gc::new(Counted(3)));
+    minor_collect();
+    assert_eq!(finalized(), 0);
+
+    // Young boxes reachable through young boxes survive too.
+    let chain = Gc::new(GcCell::new(vec![Gc::new(Counted(4))]));
+    drop(Gc::new(Counted(5)));
+    // The old `Counted(1)` is left for a full collection.
+    *cell.borrow_mut() = None;
+    minor_collect();
+    assert_eq!(finalized(), 1);
+    assert_eq!(chain.borrow()[0].0, 4);
+    assert_eq!(once.get().unwrap().0, 2);
+    assert_eq!((*unique).as_ref().unwrap().0, 3);
+
+    force_collect();
+    assert_eq!(finalized(), 2);
+}
+
+#[test]
+fn finalizers_may_resurrect_young() {
+    thread_local!(static SAVED: RefCell<Option<Gc<Counted>>> = const { RefCell::new(None) });
+
+    #[derive(Trace)]
+    struct Saver(Gc<Counted>);
+
+    impl Finalize for Saver {
+        fn finalize(&self) {
+            SAVED.with(|saved| *saved.borrow_mut() = Some(self.0.clone()));
+        }
+    }
+
+    minor_collect();
+    drop(Gc::new(Saver(Gc::new(Counted(1)))));
+    minor_collect();
+    assert_eq!(SAVED.with(|saved| saved.borrow().as_ref().unwrap().0), 1);
+}
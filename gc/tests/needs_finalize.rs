@@ -0,0 +1,60 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, Finalize, Gc, GcCell, Trace};
+
+#[derive(Trace, Finalize)]
+struct Plain {
+    n: u64,
+    name: String,
+    next: Option<Gc<Counted>>,
+    cell: GcCell<Vec<Gc<Plain>>>,
+}
+
+#[derive(Trace, Finalize)]
+enum Nested {
+    Leaf(Box<u8>),
+    Counted(u8, Counted),
+}
+
+#[test]
+fn derived_types() {
+    let plain = Plain {
+        n: 1,
+        name: String::new(),
+        next: Some(Gc::new(Counted(1))),
+        cell: GcCell::new(Vec::new()),
+    };
+    assert!(!plain.needs_finalize());
+    assert!(!Nested::Leaf(Box::new(2)).needs_finalize());
+    assert!(Nested::Counted(3, Counted(3)).needs_finalize());
+    assert!(Counted(4).needs_finalize());
+}
+
+#[test]
+fn containers() {
+    assert!(!(1u8, [2u32; 3], vec![Some(Box::new(4i64))]).needs_finalize());
+    assert!(vec![None, Some(Counted(5))].needs_finalize());
+    assert!((1u8, Counted(6)).needs_finalize());
+    assert!(GcCell::new([Counted(7)]).needs_finalize());
+    assert!(!Vec::<Counted>::new().needs_finalize());
+}
+
+#[test]
+fn still_finalizes_when_needed() {
+    let plain = Gc::new(Plain {
+        n: 1,
+        name: "plain".to_owned(),
+        next: Some(Gc::new(Counted(1))),
+        cell: GcCell::new(Vec::new()),
+    });
+    let nested = Gc::new(Nested::Counted(2, Counted(2)));
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    // The `Counted` in its own box is finalized even though `Plain` isn't.
+    drop(plain);
+    drop(nested);
+    force_collect();
+    assert_eq!(finalized(), 2);
+}
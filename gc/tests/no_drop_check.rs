@@ -0,0 +1,56 @@
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::cell::Cell;
+
+thread_local!(static DROPPED: Cell<u32> = const { Cell::new(0) });
+
+#[derive(Trace, Finalize)]
+#[unsafe_no_drop_check]
+struct Handle {
+    next: Option<Gc<Handle>>,
+    id: u32,
+}
+
+// Only touches the non-`Gc` field.
+impl Drop for Handle {
+    fn drop(&mut self) {
+        DROPPED.with(|d| d.set(d.get() + self.id));
+    }
+}
+
+#[test]
+fn runs_the_users_drop() {
+    let a = Gc::new(Handle { next: None, id: 1 });
+    let b = Gc::new(Handle {
+        next: Some(a),
+        id: 2,
+    });
+    force_collect();
+    assert_eq!(DROPPED.with(Cell::get), 0);
+
+    drop(b);
+    force_collect();
+    assert_eq!(DROPPED.with(Cell::get), 3);
+}
+
+#[derive(Trace, Finalize)]
+#[unsafe_union_trace(id)]
+#[unsafe_no_drop_check]
+union Id {
+    id: u32,
+    bytes: [u8; 4],
+}
+
+impl Drop for Id {
+    fn drop(&mut self) {
+        DROPPED.with(|d| d.set(d.get() + unsafe { self.id }));
+    }
+}
+
+#[test]
+fn runs_the_users_drop_for_unions() {
+    drop(Gc::new(Id {
+        bytes: [1, 0, 0, 0],
+    }));
+    force_collect();
+    assert_eq!(DROPPED.with(Cell::get), u32::from_ne_bytes([1, 0, 0, 0]));
+}
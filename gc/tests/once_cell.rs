@@ -0,0 +1,75 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{collect_step, force_collect, Gc, GcOnceCell};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+#[test]
+fn get_or_init_keeps_value_alive() {
+    let cell = Gc::new(GcOnceCell::new());
+    let value = cell.get_or_init(|| {
+        let value = Gc::new(Counted(1));
+        // The new value is still rooted while it's being created.
+        force_collect();
+        value
+    });
+    assert_eq!(value.0, 1);
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    // The initializer isn't run again.
+    assert_eq!(cell.get_or_init(|| unreachable!()).0, 1);
+
+    drop(cell);
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+#[test]
+fn set_only_once() {
+    let cell = Gc::new(GcOnceCell::new());
+    assert!(cell.set(Gc::new(1)).is_ok());
+    assert_eq!(*cell.set(Gc::new(2)).unwrap_err(), 2);
+    force_collect();
+    assert_eq!(**cell.get().unwrap(), 1);
+}
+
+#[test]
+fn set_during_incremental_mark() {
+    let cell = Gc::new(GcOnceCell::new());
+    let _garbage = Gc::new(0);
+    assert!(!collect_step(Duration::from_secs(0)));
+
+    // The cell may already be marked, so setting it must mark the value.
+    cell.set(Gc::new(Counted(2))).ok().unwrap();
+    while !collect_step(Duration::from_secs(1)) {}
+    assert_eq!(cell.get().unwrap().0, 2);
+    assert_eq!(finalized(), 0);
+}
+
+#[test]
+fn panicking_initializer_leaves_cell_empty() {
+    let cell = Gc::new(GcOnceCell::<Gc<u32>>::new());
+    let result = catch_unwind(AssertUnwindSafe(|| cell.get_or_init(|| panic!("init"))));
+    assert!(result.is_err());
+    assert!(cell.get().is_none());
+    assert_eq!(**cell.get_or_init(|| Gc::new(3)), 3);
+}
+
+#[test]
+#[should_panic(expected = "GcOnceCell initialized reentrantly")]
+fn reentrant_init_panics() {
+    let cell = GcOnceCell::new();
+    cell.get_or_init(|| {
+        let _ = cell.set(Gc::new(1));
+        Gc::new(2)
+    });
+}
+
+#[test]
+fn into_inner() {
+    let cell = GcOnceCell::from(Gc::new(4));
+    assert_eq!(*cell.into_inner().unwrap(), 4);
+    assert!(GcOnceCell::<Gc<u32>>::new().into_inner().is_none());
+}
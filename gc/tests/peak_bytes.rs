@@ -0,0 +1,24 @@
+#![cfg(feature = "unstable-stats")]
+
+use gc::{force_collect, reset_peak, stats, with_bulk_alloc, Gc};
+
+#[test]
+fn tracks_peak_across_collections() {
+    let base = stats().bytes_allocated;
+    let all = with_bulk_alloc(|| (0..100).map(Gc::new).collect::<Vec<_>>());
+    let peak = stats().bytes_allocated;
+    assert!(peak > base);
+    assert_eq!(stats().peak_bytes_allocated, peak);
+
+    // Freeing memory doesn't lower the peak.
+    drop(all);
+    force_collect();
+    assert!(stats().bytes_allocated < peak);
+    assert_eq!(stats().peak_bytes_allocated, peak);
+
+    reset_peak();
+    assert_eq!(stats().peak_bytes_allocated, stats().bytes_allocated);
+    let one = Gc::new(0);
+    assert_eq!(stats().peak_bytes_allocated, stats().bytes_allocated);
+    drop(one);
+}
@@ -0,0 +1,88 @@
+use gc::{force_collect, set_phase_listener, try_collect, Finalize, Gc, GcPhase, Trace};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use GcPhase::*;
+
+#[derive(Trace)]
+struct Finalized;
+
+impl Finalize for Finalized {
+    fn finalize(&self) {
+        PHASES.with(|p| p.borrow_mut().push(None));
+    }
+}
+
+// The phases reported so far, with `None` for a finalizer running.
+thread_local!(static PHASES: RefCell<Vec<Option<GcPhase>>> = const { RefCell::new(Vec::new()) });
+
+fn record() {
+    set_phase_listener(|phase| PHASES.with(|p| p.borrow_mut().push(Some(phase))));
+}
+
+fn take_phases() -> Vec<Option<GcPhase>> {
+    PHASES.with(|p| p.take())
+}
+
+#[test]
+fn marks_again_after_finalizers() {
+    record();
+    drop(Gc::new(Finalized));
+    force_collect();
+    // The phases are only delivered once the collection is done.
+    assert_eq!(
+        take_phases(),
+        [
+            None,
+            Some(MarkStart),
+            Some(MarkEnd),
+            Some(FinalizeStart),
+            Some(FinalizeEnd),
+            Some(MarkStart),
+            Some(MarkEnd),
+            Some(SweepStart),
+            Some(SweepEnd),
+        ]
+    );
+}
+
+#[test]
+fn stopped_mark_ends() {
+    let _live = Gc::new(1);
+    record();
+    assert!(!try_collect(|| false));
+    assert_eq!(take_phases(), [Some(MarkStart), Some(MarkEnd)]);
+}
+
+#[test]
+fn listener_may_replace_itself() {
+    let calls = Rc::new(Cell::new(0));
+    let counted = calls.clone();
+    set_phase_listener(move |_| {
+        counted.set(counted.get() + 1);
+        record();
+    });
+    force_collect();
+    assert_eq!(calls.get(), 1);
+    assert_eq!(take_phases().len(), 5);
+}
+
+#[test]
+fn listener_may_allocate_and_collect() {
+    let collected = Rc::new(Cell::new(false));
+    let once = collected.clone();
+    set_phase_listener(move |phase| {
+        PHASES.with(|p| p.borrow_mut().push(Some(phase)));
+        if phase == SweepEnd && !once.replace(true) {
+            let kept = Gc::new(1);
+            force_collect();
+            assert_eq!(*kept, 1);
+        }
+    });
+    force_collect();
+    assert!(collected.get());
+    // The nested collection's phases follow those of the first one.
+    let phases = take_phases();
+    assert_eq!(phases.len(), 12);
+    assert_eq!(phases[..6], phases[6..]);
+}
@@ -0,0 +1,64 @@
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::cell::Cell;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::ptr;
+
+/// Points to its own field once pinned.
+#[derive(Trace, Finalize)]
+struct SelfRef {
+    value: Gc<u32>,
+    #[unsafe_ignore_trace]
+    this: Cell<*const SelfRef>,
+    #[unsafe_ignore_trace]
+    _pinned: PhantomPinned,
+}
+
+impl SelfRef {
+    fn init(self: Pin<&Self>) {
+        self.this.set(&*self);
+    }
+
+    fn is_at_home(&self) -> bool {
+        ptr::eq(self.this.get(), self)
+    }
+}
+
+#[derive(Trace, Finalize)]
+struct Holder {
+    pinned: Pin<Gc<SelfRef>>,
+}
+
+#[test]
+fn pinned_value_stays_put() {
+    let pinned = Gc::pin(SelfRef {
+        value: Gc::new(1),
+        this: Cell::new(ptr::null()),
+        _pinned: PhantomPinned,
+    });
+    pinned.as_ref().init();
+
+    // The pinned `Gc` is traced, and its value kept alive and in place.
+    let holder = Gc::new(Holder {
+        pinned: pinned.clone(),
+    });
+    drop(pinned);
+    for _ in 0..100 {
+        Gc::new(0);
+    }
+    force_collect();
+    assert!(holder.pinned.is_at_home());
+    assert_eq!(*holder.pinned.value, 1);
+}
+
+#[test]
+fn pin_existing() {
+    let gc = Gc::new(SelfRef {
+        value: Gc::new(2),
+        this: Cell::new(ptr::null()),
+        _pinned: PhantomPinned,
+    });
+    unsafe { Gc::as_pin_ref(&gc) }.init();
+    force_collect();
+    assert!(gc.is_at_home());
+}
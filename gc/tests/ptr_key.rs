@@ -0,0 +1,66 @@
+use gc::{force_collect, Finalize, Gc, GcCell, GcPtrKey, Trace};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+#[derive(Trace, Finalize, PartialEq, Eq, Hash)]
+struct Node {
+    value: u32,
+}
+
+fn ptr_hash<T: ?Sized>(gc: &Gc<T>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    Gc::ptr_hash(gc, &mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn ptr_hash_matches_ptr_eq() {
+    let a = Gc::new(Node { value: 1 });
+    let b = Gc::new(Node { value: 1 });
+    assert_eq!(ptr_hash(&a), ptr_hash(&a.clone()));
+    assert_ne!(ptr_hash(&a), ptr_hash(&b));
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)] // the address of a `Gc` never changes
+fn equal_contents_are_distinct_keys() {
+    let a = Gc::new(Node { value: 1 });
+    let b = Gc::new(Node { value: 1 });
+    assert!(a == b);
+
+    let mut set = HashSet::new();
+    assert!(set.insert(GcPtrKey(a.clone())));
+    assert!(set.insert(GcPtrKey(b)));
+    assert!(!set.insert(GcPtrKey(a.clone())));
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&GcPtrKey(a)));
+}
+
+/// A cyclic node, which can't be hashed structurally.
+#[derive(Trace, Finalize)]
+struct Cyclic {
+    next: GcCell<Option<Gc<Cyclic>>>,
+}
+
+#[test]
+fn keys_in_gc_are_traced() {
+    let node = Gc::new(Cyclic {
+        next: GcCell::new(None),
+    });
+    *node.next.borrow_mut() = Some(node.clone());
+
+    let names = Gc::new(GcCell::new(HashMap::new()));
+    names
+        .borrow_mut()
+        .insert(GcPtrKey(node.clone()), "self-loop");
+    let key = GcPtrKey(node);
+    force_collect();
+    assert_eq!(names.borrow()[&key], "self-loop");
+
+    // The map keeps the node alive on its own.
+    drop(key);
+    force_collect();
+    let (key, _) = names.borrow_mut().drain().next().unwrap();
+    assert!(Gc::ptr_eq(&key.0, key.0.next.borrow().as_ref().unwrap()));
+}
@@ -0,0 +1,55 @@
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::rc::Rc;
+
+#[derive(Trace, Finalize, Clone)]
+struct Node {
+    value: i32,
+    child: Option<Gc<Node>>,
+}
+
+fn leaf(value: i32) -> Gc<Node> {
+    Gc::new(Node { value, child: None })
+}
+
+#[test]
+fn into_rc_when_unique() {
+    let a = Gc::new(Node {
+        value: 1,
+        child: Some(leaf(2)),
+    });
+    let rc = Gc::try_into_rc_scanning(a).unwrap();
+    force_collect();
+
+    // The child is rooted through the `Rc`.
+    assert_eq!(rc.value, 1);
+    assert_eq!(rc.child.as_ref().unwrap().value, 2);
+}
+
+#[test]
+fn not_into_rc_when_shared_by_the_heap() {
+    let a = leaf(1);
+    let parent = Gc::new(Node {
+        value: 0,
+        child: Some(a.clone()),
+    });
+    assert!(Gc::try_into_rc_scanning(a).is_none());
+    force_collect();
+    assert_eq!(parent.child.as_ref().unwrap().value, 1);
+}
+
+#[test]
+fn from_rc() {
+    let rc = Rc::new(Node {
+        value: 1,
+        child: Some(leaf(2)),
+    });
+    let shared = Gc::from_rc(rc.clone());
+    let unique = Gc::from_rc(rc);
+    force_collect();
+    assert!(!Gc::ptr_eq(&shared, &unique));
+    assert!(Gc::ptr_eq(
+        shared.child.as_ref().unwrap(),
+        unique.child.as_ref().unwrap()
+    ));
+    assert_eq!(unique.child.as_ref().unwrap().value, 2);
+}
@@ -0,0 +1,21 @@
+use gc::{force_collect, try_collect, Finalize, Gc, Trace};
+use std::cell::Cell;
+
+thread_local!(static NESTED: Cell<Option<bool>> = const { Cell::new(None) });
+
+#[derive(Trace)]
+struct Collecting;
+
+impl Finalize for Collecting {
+    fn finalize(&self) {
+        force_collect();
+        NESTED.with(|n| n.set(Some(try_collect(|| true))));
+    }
+}
+
+#[test]
+fn force_collect_in_finalizer_is_skipped() {
+    drop(Gc::new(Collecting));
+    force_collect();
+    NESTED.with(|n| assert_eq!(n.get(), Some(false)));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::finalized;
+use gc::{collect_step, force_collect, Finalize, Gc, GcCell, GcRegion, Trace};
+use std::time::Duration;
+
+#[derive(Trace, Clone)]
+struct Node {
+    children: GcCell<Vec<Gc<Node>>>,
+}
+
+impl Finalize for Node {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+fn node() -> Node {
+    Node {
+        children: GcCell::new(Vec::new()),
+    }
+}
+
+#[test]
+fn collected_separately() {
+    let region = GcRegion::new();
+    let root = unsafe { region.alloc(node()) };
+    let child = unsafe { region.alloc(node()) };
+    root.children.borrow_mut().push(child);
+    drop(unsafe { region.alloc(node()) });
+    drop(Gc::new(node()));
+
+    // Only the region's garbage is collected.
+    assert_eq!(region.collect().objects_freed, 1);
+    assert_eq!(finalized(), 1);
+
+    // And the thread's collections leave the region alone.
+    force_collect();
+    assert_eq!(finalized(), 2);
+    assert_eq!(root.children.borrow().len(), 1);
+
+    drop(root);
+    assert_eq!(region.collect().objects_freed, 2);
+    assert_eq!(finalized(), 4);
+}
+
+#[test]
+fn dropping_collects() {
+    let region = GcRegion::new();
+    let kept = unsafe { region.alloc(node()) };
+    drop(unsafe { region.alloc(node()) });
+    drop(region);
+    assert_eq!(finalized(), 1);
+    assert!(kept.children.borrow().is_empty());
+}
+
+#[test]
+fn during_incremental_collection() {
+    let live = Gc::new(node());
+    live.children.borrow_mut().push(Gc::new(node()));
+    force_collect();
+
+    let region = GcRegion::new();
+    let root = unsafe { region.alloc(node()) };
+    assert!(!collect_step(Duration::ZERO));
+
+    // Mutating a region's cell doesn't involve the thread's collection.
+    root.children
+        .borrow_mut()
+        .push(unsafe { region.alloc(node()) });
+    region.collect();
+    while !collect_step(Duration::ZERO) {}
+
+    assert_eq!(finalized(), 0);
+    assert_eq!(live.children.borrow().len(), 1);
+    assert_eq!(root.children.borrow().len(), 1);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::cell::{Cell, RefCell};
+
+thread_local!(static SAVED: RefCell<Option<Gc<Resurrected>>> = const { RefCell::new(None) });
+thread_local!(static DROPPED: Cell<usize> = const { Cell::new(0) });
+
+struct Resurrected(u32);
+
+impl Finalize for Resurrected {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+unsafe impl Trace for Resurrected {
+    gc::unsafe_empty_trace!();
+}
+
+impl Drop for Resurrected {
+    fn drop(&mut self) {
+        DROPPED.with(|d| d.set(d.get() + 1));
+    }
+}
+
+/// Resurrects the object it points to when finalized.
+#[derive(Trace)]
+struct Resurrector(Gc<Resurrected>);
+
+impl Finalize for Resurrector {
+    fn finalize(&self) {
+        SAVED.with(|s| *s.borrow_mut() = Some(self.0.clone()));
+    }
+}
+
+fn counts() -> (usize, usize) {
+    (finalized(), DROPPED.with(|d| d.get()))
+}
+
+/// Resurrects an object, then lets it die again, returning the finalized and
+/// dropped counts after each collection.
+fn resurrect_and_kill() -> [(usize, usize); 2] {
+    drop(Gc::new(Resurrector(Gc::new(Resurrected(7)))));
+    force_collect();
+    let first = counts();
+
+    let saved = SAVED.with(|s| s.borrow_mut().take()).unwrap();
+    force_collect();
+    assert_eq!(saved.0, 7);
+    drop(saved);
+    force_collect();
+    [first, counts()]
+}
+
+#[test]
+fn finalized_once_by_default() {
+    assert_eq!(resurrect_and_kill(), [(1, 0), (1, 1)]);
+}
+
+#[cfg(feature = "unstable-config")]
+#[test]
+fn refinalize_resurrected() {
+    gc::configure(|c| c.refinalize_resurrected = true);
+    assert_eq!(resurrect_and_kill(), [(1, 0), (2, 1)]);
+}
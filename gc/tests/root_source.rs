@@ -0,0 +1,67 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, register_root_source, Finalize, Gc, GcCell, GcPointer, Trace};
+use std::cell::RefCell;
+
+thread_local!(static TAGGED: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) });
+#[derive(Trace)]
+struct Object {
+    child: GcCell<Option<Gc<Object>>>,
+}
+
+impl Finalize for Object {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+fn object() -> Gc<Object> {
+    Gc::new(Object {
+        child: GcCell::new(None),
+    })
+}
+
+/// Reports every tagged pointer, i.e. with the lowest bit set, on the stack.
+fn register() {
+    register_root_source(|report| {
+        TAGGED.with(|tagged| {
+            for &value in tagged.borrow().iter() {
+                if value & 1 == 1 {
+                    report(GcPointer::from_raw((value & !1) as *const Object));
+                }
+            }
+        })
+    });
+}
+
+fn push(value: usize) {
+    TAGGED.with(|tagged| tagged.borrow_mut().push(value));
+}
+
+#[test]
+fn tagged_pointers_are_roots() {
+    register();
+    let parent = object();
+    *parent.child.borrow_mut() = Some(object());
+    push(Gc::as_ptr(&parent) as usize | 1);
+    // Untagged integers and unknown pointers are ignored.
+    push(Gc::as_ptr(&parent) as usize);
+    push(8 | 1);
+    drop(parent);
+
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    TAGGED.with(|tagged| tagged.borrow_mut().clear());
+    force_collect();
+    assert_eq!(finalized(), 2);
+}
+
+#[test]
+fn from_raw_matches_gc() {
+    let gc = Gc::new(3u16);
+    assert_eq!(GcPointer::from_raw(Gc::as_ptr(&gc)), GcPointer::from(&gc));
+    let gc = Gc::new([1u64; 4]);
+    assert_eq!(GcPointer::from_raw(Gc::as_ptr(&gc)), GcPointer::from(&gc));
+}
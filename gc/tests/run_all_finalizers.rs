@@ -0,0 +1,40 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, run_all_finalizers, Finalize, Gc, Trace};
+
+#[derive(Trace)]
+struct Resource {
+    child: Option<Gc<Resource>>,
+}
+
+impl Finalize for Resource {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+#[test]
+fn finalizes_everything_once() {
+    let live = Gc::new(Resource {
+        child: Some(Gc::new(Resource { child: None })),
+    });
+    let dead = Gc::new(Resource { child: None });
+    drop(dead);
+
+    run_all_finalizers();
+    assert_eq!(finalized(), 3);
+
+    // Nothing is freed, and nothing is finalized twice.
+    assert!(live.child.is_some());
+    run_all_finalizers();
+    force_collect();
+    drop(live);
+    force_collect();
+    assert_eq!(finalized(), 3);
+
+    // Later allocations are finalized as usual.
+    drop(Gc::new(Resource { child: None }));
+    force_collect();
+    assert_eq!(finalized(), 4);
+}
@@ -0,0 +1,34 @@
+use gc::{Gc, GcCell};
+
+/// Only compiles for `T: Send`.
+fn assert_send<T: ?Sized + Send>() {}
+
+/// Implemented twice for `Sync` types, so that naming `is_not_sync` is
+/// ambiguous, and fails to compile, unless `T` is not `Sync`.
+trait AmbiguousIfSync<A> {
+    fn is_not_sync() {}
+}
+impl<T: ?Sized> AmbiguousIfSync<()> for T {}
+impl<T: ?Sized + Sync> AmbiguousIfSync<u8> for T {}
+
+/// Likewise for `Send`.
+trait AmbiguousIfSend<A> {
+    fn is_not_send() {}
+}
+impl<T: ?Sized> AmbiguousIfSend<()> for T {}
+impl<T: ?Sized + Send> AmbiguousIfSend<u8> for T {}
+
+#[test]
+fn gc_cell_is_send_but_not_sync() {
+    assert_send::<GcCell<u8>>();
+    assert_send::<GcCell<[u8]>>();
+    <GcCell<u8> as AmbiguousIfSync<_>>::is_not_sync();
+    <GcCell<[u8]> as AmbiguousIfSync<_>>::is_not_sync();
+}
+
+#[test]
+fn gc_is_neither_send_nor_sync() {
+    <Gc<u8> as AmbiguousIfSend<_>>::is_not_send();
+    <Gc<u8> as AmbiguousIfSync<_>>::is_not_sync();
+    <GcCell<Gc<u8>> as AmbiguousIfSend<_>>::is_not_send();
+}
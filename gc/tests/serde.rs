@@ -1,6 +1,7 @@
 #![cfg(feature = "serde")]
 
-use gc::Gc;
+use gc::{with_sharing, Finalize, Gc, GcCell, SharedGc, Trace};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -21,3 +22,155 @@ fn serde_tests() {
     assert_eq!(serde_json::to_value(&expected).unwrap(), value);
     assert_eq!(serde_json::from_value::<Example>(value).unwrap(), expected);
 }
+
+#[test]
+fn serde_gc_cell() {
+    let value = json!({ "counts": [1, 2, 3] });
+
+    let cell: GcCell<HashMap<String, Vec<i32>>> = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(cell.borrow()["counts"], vec![1, 2, 3]);
+    assert_eq!(serde_json::to_value(&cell).unwrap(), value);
+
+    let _guard = cell.borrow_mut();
+    assert!(serde_json::to_value(&cell).is_err());
+}
+
+#[test]
+fn shared_gc_keeps_sharing() {
+    let shared = SharedGc(Gc::new(vec![1]));
+    let root = SharedGc(Gc::new(vec![
+        shared.clone(),
+        SharedGc(Gc::new(vec![2])),
+        shared,
+    ]));
+    let value = serde_json::to_value(&root).unwrap();
+    assert_eq!(
+        value,
+        json!({"Def": [{"Def": [1]}, {"Def": [2]}, {"Ref": 1}]})
+    );
+
+    let root: SharedGc<Vec<SharedGc<Vec<i32>>>> = serde_json::from_value(value).unwrap();
+    let children = &root.0;
+    assert_eq!(children.len(), 3);
+    assert!(Gc::ptr_eq(&children[0].0, &children[2].0));
+    assert!(!Gc::ptr_eq(&children[0].0, &children[1].0));
+    assert_eq!(*children[1].0, [2]);
+
+    // Numbering starts again for each outermost `SharedGc`.
+    let again = serde_json::to_value(&root).unwrap();
+    assert_eq!(again["Def"][2], json!({"Ref": 1}));
+}
+
+/// A node which may point back to itself.
+#[derive(Trace, Finalize)]
+struct Cyclic(GcCell<Option<SharedGc<Cyclic>>>);
+
+impl Serialize for Cyclic {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cyclic {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        GcCell::deserialize(deserializer).map(Cyclic)
+    }
+}
+
+#[test]
+fn shared_gc_cycles() {
+    let a = SharedGc(Gc::new(Cyclic(GcCell::new(None))));
+    *a.0 .0.borrow_mut() = Some(a.clone());
+    let value = serde_json::to_value(&a).unwrap();
+    assert_eq!(value, json!({"Def": {"Ref": 0}}));
+    *a.0 .0.borrow_mut() = None;
+
+    let err = serde_json::from_value::<SharedGc<Cyclic>>(value)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("cycles can't be deserialized"));
+}
+
+#[test]
+fn shared_gc_errors() {
+    let err = serde_json::from_value::<SharedGc<i32>>(json!({"Ref": 0}))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("referenced before it is defined"));
+
+    let err = with_sharing(|| {
+        serde_json::from_value::<(SharedGc<i32>, SharedGc<u8>)>(json!([
+            {"Def": 1},
+            {"Ref": 0},
+        ]))
+    })
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("has a different type"));
+}
+
+#[test]
+fn with_sharing_across_values() {
+    let shared = SharedGc(Gc::new(3));
+    let (a, b) = with_sharing(|| {
+        (
+            serde_json::to_value(&shared).unwrap(),
+            serde_json::to_value(&shared).unwrap(),
+        )
+    });
+    assert_eq!(b, json!({"Ref": 0}));
+
+    let (a, b): (SharedGc<i32>, SharedGc<i32>) = with_sharing(|| {
+        (
+            serde_json::from_value(a).unwrap(),
+            serde_json::from_value(b).unwrap(),
+        )
+    });
+    assert!(Gc::ptr_eq(&a.0, &b.0));
+}
+
+/// A node of a graph, whose children may be shared.
+#[derive(Trace, Finalize)]
+struct Node {
+    name: String,
+    children: Vec<SharedGc<Node>>,
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.name, &self.children).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (name, children) = Deserialize::deserialize(deserializer)?;
+        Ok(Node { name, children })
+    }
+}
+
+fn node(name: &str, children: Vec<SharedGc<Node>>) -> SharedGc<Node> {
+    SharedGc(Gc::new(Node {
+        name: name.to_string(),
+        children,
+    }))
+}
+
+#[test]
+fn shared_gc_diamond() {
+    let bottom = node("bottom", vec![]);
+    let left = node("left", vec![bottom.clone()]);
+    let right = node("right", vec![bottom]);
+    let top = node("top", vec![left, right]);
+
+    let value = serde_json::to_value(&top).unwrap();
+    // The bottom is only written once, as the third allocation.
+    assert_eq!(value["Def"][1][1]["Def"][1][0], json!({"Ref": 2}));
+    assert_eq!(value.to_string().matches("bottom").count(), 1);
+
+    let top: SharedGc<Node> = serde_json::from_value(value).unwrap();
+    let (left, right) = (&top.0.children[0].0, &top.0.children[1].0);
+    assert_eq!((&*left.name, &*right.name), ("left", "right"));
+    assert!(Gc::ptr_eq(&left.children[0].0, &right.children[0].0));
+    assert_eq!(left.children[0].0.name, "bottom");
+}
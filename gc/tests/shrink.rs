@@ -0,0 +1,30 @@
+#![cfg(feature = "unstable-config")]
+
+use gc::{configure, force_collect, shrink_to_fit, Gc};
+
+fn threshold() -> usize {
+    let mut threshold = 0;
+    configure(|config| threshold = config.threshold);
+    threshold
+}
+
+#[test]
+fn shrink_after_spike() {
+    let spike: Vec<_> = (0..10_000).map(Gc::new).collect();
+    let grown = threshold();
+    assert!(grown > 100_000);
+
+    drop(spike);
+    force_collect();
+    // Collecting alone doesn't lower the threshold.
+    assert_eq!(threshold(), grown);
+
+    // It isn't lowered below the configured minimum.
+    configure(|config| config.min_threshold = 5000);
+    shrink_to_fit();
+    assert_eq!(threshold(), 5000);
+
+    configure(|config| config.min_threshold = 100);
+    shrink_to_fit();
+    assert_eq!(threshold(), 100);
+}
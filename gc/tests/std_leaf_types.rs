@@ -0,0 +1,39 @@
+use gc::{Finalize, Gc, Trace};
+use std::ffi::{CString, OsString};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime};
+
+#[derive(Trace, Finalize)]
+struct Request {
+    started: Instant,
+    received: SystemTime,
+    timeout: Duration,
+    peer: SocketAddr,
+    ip: IpAddr,
+    arg: OsString,
+    name: CString,
+    body: Gc<String>,
+}
+
+#[test]
+fn derive_with_std_leaf_types() {
+    let request = Gc::new(Request {
+        started: Instant::now(),
+        received: SystemTime::now(),
+        timeout: Duration::from_secs(1),
+        peer: SocketAddr::from((Ipv4Addr::LOCALHOST, 80)),
+        ip: IpAddr::from(Ipv4Addr::LOCALHOST),
+        arg: OsString::from("-v"),
+        name: CString::new("gc").unwrap(),
+        body: Gc::new("hello".to_owned()),
+    });
+    gc::force_collect();
+    assert_eq!(*request.body, "hello");
+    assert_eq!(request.timeout, Duration::from_secs(1));
+}
+
+#[test]
+fn unsized_leaf_types() {
+    let _: Gc<Box<std::ffi::OsStr>> = Gc::new(OsString::from("a").into_boxed_os_str());
+    let _: Gc<Box<std::ffi::CStr>> = Gc::new(CString::new("a").unwrap().into_boxed_c_str());
+}
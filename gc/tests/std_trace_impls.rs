@@ -0,0 +1,230 @@
+//! Checks every `Trace` impl for std containers traces, roots, unroots and
+//! finalizes each of its elements exactly once.
+
+mod common;
+
+use common::finalized;
+use gc::{force_collect, Finalize, Gc, GcCell, Trace};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::hash::BuildHasher;
+use std::num::Wrapping;
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+
+thread_local!(static TRACED: Cell<usize> = const { Cell::new(0) });
+/// Counts how many times it is traced and finalized.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Tracked(usize);
+
+impl Finalize for Tracked {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+unsafe impl Trace for Tracked {
+    unsafe fn trace(&self) {
+        TRACED.with(|t| t.set(t.get() + 1));
+    }
+    unsafe fn root(&self) {}
+    unsafe fn unroot(&self) {}
+    fn finalize_glue(&self) {
+        Finalize::finalize(self);
+    }
+}
+
+/// A container element holding one `Tracked` inline and one behind a `Gc`.
+///
+/// Visiting the element twice is caught by the inline counters, and by the
+/// double-root assertions in `Gc`. Not visiting it is caught by the `Gc`
+/// being collected too early or not at all.
+#[derive(Trace, Finalize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Elem {
+    gc: Gc<Tracked>,
+    inline: Tracked,
+}
+
+/// A hasher which doesn't implement `Trace`.
+#[derive(Default)]
+struct Untraced(RandomState);
+
+impl BuildHasher for Untraced {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        self.0.build_hasher()
+    }
+}
+
+fn check<C: Trace + 'static>(make: impl FnOnce(&mut dyn FnMut() -> Elem) -> C) {
+    let mut n = 0;
+    let container = make(&mut || {
+        n += 1;
+        Elem {
+            gc: Gc::new(Tracked(n)),
+            inline: Tracked(n),
+        }
+    });
+    let outer = Gc::new(container);
+
+    // Allocating may have already triggered a collection.
+    TRACED.with(|t| t.set(0));
+    assert_eq!(finalized(), 0, "finalized while reachable");
+
+    force_collect();
+    TRACED.with(|t| assert_eq!(t.get(), 2 * n, "traced"));
+    assert_eq!(finalized(), 0, "finalized while reachable");
+
+    drop(outer);
+    force_collect();
+    TRACED.with(|t| assert_eq!(t.get(), 2 * n, "traced after death"));
+    assert_eq!(finalized(), 2 * n, "finalized");
+}
+
+macro_rules! trace_cases {
+    ($($name:ident: |$e:ident| $make:expr;)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check(|$e| $make);
+            }
+        )*
+    };
+}
+
+trace_cases! {
+    gc: |e| Gc::new(e());
+    gc_cell: |e| GcCell::new(e());
+    boxed: |e| Box::new(e());
+    boxed_slice: |e| vec![e(), e()].into_boxed_slice();
+    array: |e| [e(), e(), e()];
+    empty_array: |_e| [0u8; 0];
+    tuple: |e| (e(), 1u8, e());
+    option: |e| Some(e());
+    result_ok: |e| Ok::<_, Elem>(e());
+    result_err: |e| Err::<Elem, _>(e());
+    vec: |e| vec![e(), e()];
+    vec_into_iter: |e| vec![e(), e()].into_iter();
+    array_into_iter: |e| IntoIterator::into_iter([e(), e()]);
+    vec_deque: |e| VecDeque::from(vec![e(), e()]);
+    linked_list: |e| LinkedList::from([e(), e()]);
+    binary_heap: |e| BinaryHeap::from(vec![e(), e()]);
+    btree_set: |e| BTreeSet::from([e(), e()]);
+    btree_map: |e| BTreeMap::from([(e(), e()), (e(), e())]);
+    hash_set: |e| HashSet::from([e(), e()]);
+    hash_map: |e| HashMap::from([(e(), e()), (e(), e())]);
+    hash_set_untraced_hasher: |e| vec![e(), e()].into_iter().collect::<HashSet<_, Untraced>>();
+    hash_map_untraced_hasher: |e| {
+        vec![(e(), e()), (e(), e())].into_iter().collect::<HashMap<_, _, Untraced>>()
+    };
+    cow_owned: |e| Cow::<'static, [Elem]>::Owned(vec![e(), e()]);
+    range: |e| e()..e();
+    range_from: |e| e()..;
+    range_inclusive: |e| e()..=e();
+    range_to: |e| ..e();
+    range_to_inclusive: |e| ..=e();
+    wrapping: |e| Wrapping(e());
+    reverse: |e| Reverse(e());
+    ordering: |_e| Ordering::Less;
+}
+
+/// Lazily yields the elements of a vector.
+#[derive(Trace, Finalize)]
+struct Lazy {
+    rest: std::vec::IntoIter<Gc<Tracked>>,
+}
+
+#[test]
+fn vec_into_iter_traces_remaining() {
+    let mut rest = vec![
+        Gc::new(Tracked(1)),
+        Gc::new(Tracked(2)),
+        Gc::new(Tracked(3)),
+    ]
+    .into_iter();
+    let first = rest.next();
+    let lazy = Gc::new(Lazy { rest });
+    drop(first);
+    force_collect();
+    assert_eq!(finalized(), 1, "finalized");
+
+    TRACED.with(|t| t.set(0));
+    force_collect();
+    TRACED.with(|t| assert_eq!(t.get(), 2, "traced"));
+
+    drop(lazy);
+    force_collect();
+    assert_eq!(finalized(), 3, "finalized after death");
+}
+
+/// Holds borrowed and owned strings and slices.
+#[derive(Trace, Finalize)]
+struct Names<'a> {
+    name: Cow<'static, str>,
+    alias: Cow<'a, str>,
+    elems: Cow<'static, [Elem]>,
+}
+
+#[test]
+fn derive_with_cow_fields() {
+    let alias = String::from("alias");
+    let names = Names {
+        name: Cow::Borrowed("name"),
+        alias: Cow::Borrowed(&alias),
+        elems: Cow::Owned(vec![Elem {
+            gc: Gc::new(Tracked(1)),
+            inline: Tracked(2),
+        }]),
+    };
+    force_collect();
+    assert_eq!(finalized(), 0, "finalized");
+    assert_eq!(names.name, "name");
+    assert_eq!(names.alias, "alias");
+
+    let names: Gc<Names<'static>> = Gc::new(Names {
+        name: Cow::Owned(String::from("owned")),
+        alias: Cow::Borrowed("static"),
+        elems: Cow::Borrowed(&[]),
+    });
+    force_collect();
+    assert_eq!(names.name, "owned");
+}
+
+struct Foo {
+    gc: Gc<Tracked>,
+}
+
+/// Points back to a value outside the garbage-collected heap.
+#[derive(Trace, Finalize)]
+struct Child {
+    parent: rc::Weak<RefCell<Foo>>,
+    shared: sync::Weak<str>,
+}
+
+#[test]
+fn derive_with_weak_fields() {
+    let parent = Rc::new(RefCell::new(Foo {
+        gc: Gc::new(Tracked(1)),
+    }));
+    let shared: Arc<str> = "shared".into();
+    let child = Gc::new(Child {
+        parent: Rc::downgrade(&parent),
+        shared: Arc::downgrade(&shared),
+    });
+
+    // The parent's `Gc` is kept rooted by the parent itself.
+    force_collect();
+    assert_eq!(finalized(), 0, "finalized");
+    let upgraded = child.parent.upgrade().unwrap();
+    assert_eq!(upgraded.borrow().gc.0, 1);
+    assert_eq!(&*child.shared.upgrade().unwrap(), "shared");
+
+    drop((upgraded, parent));
+    force_collect();
+    assert_eq!(finalized(), 1, "finalized after death");
+    assert!(child.parent.upgrade().is_none());
+}
@@ -0,0 +1,47 @@
+use gc::{force_collect, Finalize, Gc, Trace};
+
+#[derive(Trace, Finalize)]
+struct Cons {
+    head: usize,
+    tail: Option<Gc<Cons>>,
+}
+
+fn list(len: usize) -> Gc<Cons> {
+    let mut list = Gc::new(Cons {
+        head: len - 1,
+        tail: None,
+    });
+    for head in (0..len - 1).rev() {
+        list = Gc::new(Cons {
+            head,
+            tail: Some(list),
+        });
+    }
+    list
+}
+
+#[test]
+fn walks_without_rooting() {
+    let list = list(1_000);
+    let mut count = 0;
+    for (i, cons) in Gc::successors(&list, |c| c.tail.as_ref()).enumerate() {
+        assert_eq!(cons.head, i);
+        // Only the head of the list is rooted.
+        #[cfg(feature = "unstable-stats")]
+        assert_eq!(Gc::root_count(cons), usize::from(i == 0));
+        if i % 100 == 0 {
+            force_collect();
+        }
+        count += 1;
+    }
+    assert_eq!(count, 1_000);
+}
+
+#[test]
+fn single() {
+    let one = list(1);
+    let mut iter = Gc::successors(&one, |c| c.tail.as_ref());
+    assert!(Gc::ptr_eq(iter.next().unwrap(), &one));
+    assert!(iter.next().is_none());
+    assert!(iter.next().is_none());
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, trace_enum, Finalize, Gc, GcCell, Trace};
+
+struct Counted;
+
+impl Finalize for Counted {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+unsafe impl Trace for Counted {
+    gc::unsafe_empty_trace!();
+}
+
+enum Value {
+    Pair(Gc<Counted>, Counted),
+    Named {
+        value: GcCell<Option<Gc<Value>>>,
+        _name: &'static str,
+    },
+    Nil,
+}
+
+impl Finalize for Value {}
+
+unsafe impl Trace for Value {
+    trace_enum!(Value {
+        Pair(a, b),
+        Named { value },
+        Nil
+    });
+}
+
+enum Never {}
+
+impl Finalize for Never {}
+
+unsafe impl Trace for Never {
+    trace_enum!(Never {});
+}
+
+#[test]
+fn traces_listed_fields() {
+    let pair = Gc::new(Value::Pair(Gc::new(Counted), Counted));
+    let named = Gc::new(Value::Named {
+        value: GcCell::new(None),
+        _name: "named",
+    });
+    if let Value::Named { value, .. } = &*named {
+        *value.borrow_mut() = Some(pair);
+    }
+    let _nil = Gc::new(Value::Nil);
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    // Both the `Gc` and the inline field are finalized.
+    drop(named);
+    force_collect();
+    assert_eq!(finalized(), 2);
+
+    let _never: Option<Gc<Never>> = None;
+}
@@ -1,8 +1,9 @@
 use gc::{Finalize, Trace};
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
-thread_local!(static X: RefCell<u8> = RefCell::new(0));
+thread_local!(static X: RefCell<u8> = const { RefCell::new(0) });
 
 #[derive(Copy, Clone, Finalize)]
 struct Foo;
@@ -39,6 +40,11 @@ struct InnerRcStr {
     inner: Rc<str>,
 }
 
+#[derive(Trace, Clone, Finalize)]
+struct InnerArcStr {
+    inner: Arc<str>,
+}
+
 #[derive(Trace, Finalize)]
 struct Baz {
     a: Bar,
@@ -60,6 +66,10 @@ fn test() {
             inner: "abc".into(),
         }
         .trace();
+        InnerArcStr {
+            inner: "abc".into(),
+        }
+        .trace();
     }
 
     let bar = Bar { inner: Foo };
@@ -0,0 +1,85 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, Finalize, Gc, GcCell, Trace, TracedFn};
+
+#[derive(Trace)]
+struct Counter {
+    count: GcCell<u32>,
+}
+
+impl Finalize for Counter {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+type Callback = TracedFn<Gc<Counter>, u32, u32>;
+
+#[test]
+fn captured_gcs_are_traced() {
+    let increment: Gc<Callback> = Gc::new(TracedFn::new(
+        Gc::new(Counter {
+            count: GcCell::new(0),
+        }),
+        |counter, n| {
+            *counter.count.borrow_mut() += n;
+            *counter.count.borrow()
+        },
+    ));
+
+    force_collect();
+    assert_eq!(finalized(), 0);
+    assert_eq!(increment.call(2), 2);
+    assert_eq!(increment.call(3), 5);
+
+    drop(increment);
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+/// An object whose method is a callback referring back to the object.
+#[derive(Trace, Finalize)]
+struct Button {
+    clicks: GcCell<u32>,
+    on_click: GcCell<Option<TracedFn<Gc<Button>, (), ()>>>,
+}
+
+#[test]
+fn cyclic_environment_is_collected() {
+    let button = Gc::new(Button {
+        clicks: GcCell::new(0),
+        on_click: GcCell::new(None),
+    });
+    *button.on_click.borrow_mut() = Some(TracedFn::new(button.clone(), |button, ()| {
+        *button.clicks.borrow_mut() += 1;
+    }));
+
+    button.on_click.borrow().as_ref().unwrap().call(());
+    assert_eq!(*button.clicks.borrow(), 1);
+
+    force_collect();
+    drop(button);
+    assert_eq!(gc::force_collect_reporting().objects_freed, 1);
+}
+
+#[test]
+fn ignored_closure_without_gcs() {
+    #[derive(Trace, Finalize)]
+    struct Handler {
+        counter: Gc<Counter>,
+        // Captures no `Gc`s.
+        #[unsafe_ignore_trace]
+        scale: Box<dyn Fn(u32) -> u32>,
+    }
+
+    let factor = 3;
+    let handler = Gc::new(Handler {
+        counter: Gc::new(Counter {
+            count: GcCell::new(2),
+        }),
+        scale: Box::new(move |n| n * factor),
+    });
+    force_collect();
+    assert_eq!((handler.scale)(*handler.counter.count.borrow()), 6);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::finalized;
+use gc::{force_collect, try_collect, Finalize, Gc, GcCell, Trace};
+use std::cell::Cell;
+
+#[derive(Trace)]
+struct Node {
+    next: GcCell<Option<Gc<Node>>>,
+}
+
+impl Finalize for Node {
+    fn finalize(&self) {
+        common::count_finalized();
+    }
+}
+
+fn node(next: Option<Gc<Node>>) -> Gc<Node> {
+    Gc::new(Node {
+        next: GcCell::new(next),
+    })
+}
+
+#[test]
+fn stopping_leaves_the_heap_unchanged() {
+    let roots: Vec<_> = (0..4).map(|_| node(Some(node(None)))).collect();
+    let a = node(None);
+    let b = node(Some(a.clone()));
+    *a.next.borrow_mut() = Some(b.clone());
+    drop((a, b));
+
+    // Stop after tracing from two of the roots.
+    let calls = Cell::new(0);
+    assert!(!try_collect(|| {
+        calls.set(calls.get() + 1);
+        calls.get() <= 2
+    }));
+    assert_eq!(calls.get(), 3);
+    assert_eq!(finalized(), 0);
+
+    // No marks are left behind to keep the dropped roots alive.
+    drop(roots);
+    assert!(try_collect(|| true));
+    assert_eq!(finalized(), 10);
+}
+
+#[test]
+fn reentrant_does_nothing() {
+    #[derive(Trace)]
+    struct Collector;
+
+    impl Finalize for Collector {
+        fn finalize(&self) {
+            assert!(!try_collect(|| true));
+        }
+    }
+
+    drop(Gc::new(Collector));
+    force_collect();
+}
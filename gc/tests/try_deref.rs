@@ -0,0 +1,68 @@
+use gc::{force_collect, Finalize, Gc, Trace};
+use std::cell::Cell;
+
+thread_local!(static SEEN: Cell<Option<bool>> = const { Cell::new(None) });
+
+#[derive(Trace, Finalize)]
+struct Leaf;
+
+struct Probe(Gc<Leaf>);
+
+impl Finalize for Probe {}
+
+unsafe impl Trace for Probe {
+    gc::custom_trace!(this, {
+        mark(&this.0);
+    });
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        SEEN.with(|seen| seen.set(Some(Gc::try_deref(&self.0).is_some())));
+    }
+}
+
+#[test]
+fn try_deref_during_sweep() {
+    let probe = Gc::new(Probe(Gc::new(Leaf)));
+    assert!(Gc::try_deref(&probe.0).is_some());
+
+    drop(probe);
+    force_collect();
+    SEEN.with(|seen| assert_eq!(seen.get(), Some(false)));
+}
+
+#[test]
+fn try_deref_rooted_during_sweep() {
+    let leaf = Gc::new(Leaf);
+    drop(Gc::new(Probe(leaf.clone())));
+    force_collect();
+
+    // The probe's own `Gc<Leaf>` is not a root, so it can't be dereferenced
+    // while sweeping, even though the leaf itself is still alive.
+    SEEN.with(|seen| assert_eq!(seen.get(), Some(false)));
+    assert!(Gc::try_deref(&leaf).is_some());
+}
+
+struct Deref(Gc<u32>);
+
+impl Finalize for Deref {}
+
+unsafe impl Trace for Deref {
+    gc::custom_trace!(this, {
+        mark(&this.0);
+    });
+}
+
+impl Drop for Deref {
+    fn drop(&mut self) {
+        let _ = *self.0;
+    }
+}
+
+#[test]
+#[should_panic(expected = "dereferenced a Gc<u32> while the garbage collector was freeing objects")]
+fn deref_during_sweep_panics() {
+    drop(Gc::new(Deref(Gc::new(0))));
+    force_collect();
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{force_collect, AllocError, Finalize, Gc, Trace};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Fails large allocations while `FAIL_LARGE` is set.
+struct FailingAlloc;
+
+static FAIL_LARGE: AtomicBool = AtomicBool::new(false);
+const LARGE: usize = 1 << 16;
+
+unsafe impl GlobalAlloc for FailingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() >= LARGE && FAIL_LARGE.load(Ordering::SeqCst) {
+            ptr::null_mut()
+        } else {
+            System.alloc(layout)
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: FailingAlloc = FailingAlloc;
+
+#[derive(Trace, Finalize)]
+struct Large(#[unsafe_ignore_trace] [u8; LARGE], Gc<Counted>);
+
+#[test]
+fn try_new_reports_failure() {
+    let ok = Gc::try_new(Large([1; LARGE], Gc::new(Counted(0)))).unwrap();
+    assert_eq!(ok.0[0], 1);
+
+    let value = Large([2; LARGE], Gc::new(Counted(0)));
+    FAIL_LARGE.store(true, Ordering::SeqCst);
+    let failed = Gc::try_new(value);
+    FAIL_LARGE.store(false, Ordering::SeqCst);
+    assert_eq!(failed.err(), Some(AllocError));
+
+    // The value's `Gc` was dropped with it, still rooted.
+    force_collect();
+    assert_eq!(finalized(), 1);
+    drop(ok);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{finalized, Counted};
+use gc::{collect_step, force_collect, Finalize, Gc, GcCell, Trace};
+use std::cell::Cell;
+use std::time::Duration;
+
+thread_local!(static DROPPED: Cell<u32> = const { Cell::new(0) });
+
+#[derive(Trace, Finalize)]
+struct Node {
+    value: Gc<Counted>,
+    next: GcCell<Option<Gc<Node>>>,
+}
+
+#[test]
+fn write_then_assume_init() {
+    let mut node = Gc::<Node>::new_uninit();
+    // Nothing is traced or finalized while the node is uninitialized.
+    force_collect();
+
+    Gc::get_mut_scanning(&mut node).unwrap().write(Node {
+        value: Gc::new(Counted(1)),
+        next: GcCell::new(None),
+    });
+    force_collect();
+    assert_eq!(finalized(), 0);
+
+    let node = unsafe { Gc::assume_init(node) };
+    // The value is no longer a root, but is reachable through the node.
+    force_collect();
+    assert_eq!(node.value.0, 1);
+    assert_eq!(finalized(), 0);
+
+    // Cycles through the initialized node are collected.
+    *node.next.borrow_mut() = Some(node.clone());
+    drop(node);
+    force_collect();
+    assert_eq!(finalized(), 1);
+}
+
+#[test]
+fn zeroed() {
+    let zero = Gc::<[u64; 4]>::new_zeroed();
+    force_collect();
+    assert_eq!(*unsafe { Gc::assume_init(zero) }, [0; 4]);
+}
+
+#[test]
+fn dropped_uninit_is_freed_without_dropping() {
+    struct Loud;
+    impl Finalize for Loud {}
+    unsafe impl Trace for Loud {
+        gc::unsafe_empty_trace!();
+    }
+    impl Drop for Loud {
+        fn drop(&mut self) {
+            DROPPED.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    drop(Gc::<Loud>::new_uninit());
+    force_collect();
+    assert_eq!(DROPPED.with(|d| d.get()), 0);
+
+    let mut loud = Gc::<Loud>::new_uninit();
+    Gc::get_mut_scanning(&mut loud).unwrap().write(Loud);
+    drop(unsafe { Gc::assume_init(loud) });
+    force_collect();
+    assert_eq!(DROPPED.with(|d| d.get()), 1);
+}
+
+#[test]
+fn assume_init_during_incremental_mark() {
+    let mut node = Gc::<Node>::new_uninit();
+    let _garbage = Gc::new(0);
+    assert!(!collect_step(Duration::from_secs(0)));
+
+    Gc::get_mut_scanning(&mut node).unwrap().write(Node {
+        value: Gc::new(Counted(2)),
+        next: GcCell::new(None),
+    });
+    // The node may already be marked, so initializing it must mark the value.
+    let node = unsafe { Gc::assume_init(node) };
+    while !collect_step(Duration::from_secs(1)) {}
+    assert_eq!(node.value.0, 2);
+    assert_eq!(finalized(), 0);
+}
+
+#[test]
+fn get_mut_requires_unique() {
+    let mut x = Gc::new(1);
+    let holder = Gc::new(GcCell::new(None));
+    *holder.borrow_mut() = Some(x.clone());
+    assert!(Gc::get_mut_scanning(&mut x).is_none());
+
+    *holder.borrow_mut() = None;
+    *Gc::get_mut_scanning(&mut x).unwrap() += 1;
+    assert_eq!(*x, 2);
+}
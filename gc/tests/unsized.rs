@@ -1,4 +1,4 @@
-use gc::{Finalize, Gc, Trace};
+use gc::{force_collect, Finalize, Gc, GcCell, Trace};
 
 trait Foo: Trace {}
 
@@ -20,6 +20,38 @@ fn gc_dyn_foo() {
     let _: Gc<dyn Foo> = Gc::new(Bar);
 }
 
+#[derive(Trace, Finalize)]
+struct Holder(Gc<u32>);
+impl Foo for Holder {}
+
+#[derive(Trace, Finalize)]
+struct Borrowing<'a>(&'a u32, Gc<u32>);
+impl Foo for Borrowing<'_> {}
+
+fn children(value: &dyn Trace) -> usize {
+    let mut n = 0;
+    unsafe { value.visit_children(&mut |_| n += 1) };
+    n
+}
+
+#[test]
+fn gc_box_dyn_foo_traces_contents() {
+    let foo: Box<dyn Foo> = Box::new(Holder(Gc::new(7)));
+    assert_eq!(children(&foo), 1);
+    let foo = Gc::new(foo);
+    force_collect();
+    assert_eq!(children(&*foo), 1);
+}
+
+#[test]
+fn box_dyn_foo_non_static() {
+    let local = 7u32;
+    let scoped: Box<dyn Foo + '_> = Box::new(Borrowing(&local, Gc::new(local)));
+    assert_eq!(children(&scoped), 1);
+    force_collect();
+    assert_eq!(children(&scoped), 1);
+}
+
 #[allow(dead_code)]
 fn gc_box_anyfoo(b: Box<AnyFoo>) -> Gc<Box<AnyFoo>> {
     Gc::new(b)
@@ -30,19 +62,64 @@ fn gc_box_slice() {
     let _: Gc<Box<[u32]>> = Gc::new(Box::new([0, 1, 2]));
 }
 
+#[test]
+fn gc_box_slice_of_gcs() {
+    let values: Box<[Gc<i32>]> = vec![Gc::new(1), Gc::new(2)].into_boxed_slice();
+    let values = Gc::new(values);
+    force_collect();
+    assert_eq!(values.iter().map(|v| **v).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(children(&*values), 2);
+}
+
 #[cfg(feature = "nightly")]
 #[test]
 fn gc_slice() {
     let _: Gc<[u32]> = Gc::new([0, 1, 2]);
 }
 
+#[test]
+fn gc_slice_default() {
+    #[derive(Default, Trace, Finalize)]
+    struct Args {
+        values: Gc<[Gc<u32>]>,
+        name: Gc<str>,
+    }
+
+    let args = Args::default();
+    let copy = Gc::new(Args {
+        values: args.values.clone(),
+        name: args.name.clone(),
+    });
+    drop(args);
+    force_collect();
+    assert!(copy.values.is_empty());
+    assert_eq!(&*copy.name, "");
+}
+
 #[test]
 fn gc_box_str() {
     let _: Gc<Box<str>> = Gc::new(Box::from("hello"));
 }
 
-#[cfg(feature = "nightly")]
-#[allow(dead_code)]
-fn gc_str(_: Gc<str>) {
-    // no way to construct this yet
+#[test]
+fn gc_cell_into_boxed_slice() {
+    let cell: Box<GcCell<[Gc<u32>]>> = Box::new(GcCell::new([Gc::new(1), Gc::new(2)]));
+    let values = GcCell::into_boxed_inner(cell);
+    force_collect();
+    assert_eq!(values.iter().map(|v| **v).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(children(&values), 2);
+}
+
+#[test]
+fn gc_cell_into_boxed_empty_slice() {
+    let cell: Box<GcCell<[Gc<u32>]>> = Box::new(GcCell::new([]));
+    assert!(GcCell::into_boxed_inner(cell).is_empty());
+}
+
+#[test]
+fn gc_cell_into_boxed_dyn_foo() {
+    let cell: Box<GcCell<dyn Foo>> = Box::new(GcCell::new(Holder(Gc::new(7))));
+    let foo: Box<dyn Foo> = GcCell::into_boxed_inner(cell);
+    force_collect();
+    assert_eq!(children(&foo), 1);
 }
@@ -0,0 +1,55 @@
+use gc::{Finalize, Gc, GcCell, GcPointer, Trace};
+
+#[derive(Trace, Finalize)]
+struct Derived {
+    a: Gc<i32>,
+    list: Vec<Gc<i32>>,
+    cell: GcCell<Option<Gc<i32>>>,
+    wrapped: Manual,
+    #[unsafe_ignore_trace]
+    ignored: Gc<i32>,
+}
+
+/// Relies on the default `visit_children`.
+struct Manual(Box<Gc<i32>>);
+
+impl Finalize for Manual {}
+unsafe impl Trace for Manual {
+    gc::custom_trace!(this, {
+        mark(&this.0);
+    });
+}
+
+fn visit(value: &impl Trace) -> Vec<GcPointer> {
+    let mut children = Vec::new();
+    unsafe { value.visit_children(&mut |child| children.push(child)) };
+    children
+}
+
+#[test]
+fn visits_every_gc() {
+    let a = Gc::new(1);
+    let b = Gc::new(2);
+    let c = Gc::new(3);
+    let d = Gc::new(4);
+    let value = Derived {
+        a: a.clone(),
+        list: vec![b.clone(), a.clone()],
+        cell: GcCell::new(Some(c.clone())),
+        wrapped: Manual(Box::new(d.clone())),
+        ignored: Gc::new(5),
+    };
+
+    let expected: Vec<GcPointer> = [&a, &b, &a, &c, &d].iter().map(|&gc| gc.into()).collect();
+    assert_eq!(visit(&value), expected);
+    assert!(!visit(&value).contains(&GcPointer::from(&value.ignored)));
+    assert_eq!(visit(&Gc::new(value)).len(), 1);
+}
+
+#[test]
+fn skips_mutably_borrowed_cells() {
+    let cell = GcCell::new(vec![Gc::new(1)]);
+    assert_eq!(visit(&cell).len(), 1);
+    let _guard = cell.borrow_mut();
+    assert!(visit(&cell).is_empty());
+}
@@ -1,19 +1,83 @@
+use proc_macro2::TokenStream;
 use quote::quote;
-use synstructure::{decl_derive, AddBounds, Structure};
+use syn::{Data, DataUnion, DeriveInput, Ident};
+use synstructure::{AddBounds, Structure};
 
-decl_derive!([Trace, attributes(unsafe_ignore_trace)] => derive_trace);
+// synstructure doesn't support unions, so they are handled before building a
+// `Structure`.
+#[proc_macro_derive(
+    Trace,
+    attributes(unsafe_ignore_trace, unsafe_union_trace, unsafe_no_drop_check)
+)]
+#[allow(non_snake_case)]
+pub fn Trace(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let result = match &input.data {
+        Data::Union(data) => derive_union_trace(&input, data),
+        _ => Structure::try_new(&input).map(derive_trace),
+    };
+    result.unwrap_or_else(|e| e.to_compile_error()).into()
+}
 
-fn derive_trace(mut s: Structure<'_>) -> proc_macro2::TokenStream {
+fn derive_trace(mut s: Structure<'_>) -> TokenStream {
+    let no_drop_check = s
+        .ast()
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("unsafe_no_drop_check"));
     s.filter(|bi| {
         !bi.ast()
             .attrs
             .iter()
             .any(|attr| attr.path().is_ident("unsafe_ignore_trace"))
     });
+    s.add_bounds(AddBounds::Fields);
+
+    // Fieldless enums and unit structs have nothing to trace, so skip
+    // generating matches over their variants.
+    let trace_impl = if s.variants().iter().all(|v| v.bindings().is_empty()) {
+        s.unsafe_bound_impl(quote!(::gc::Trace), quote!(::gc::unsafe_empty_trace!();))
+    } else {
+        derive_trace_impl(&s)
+    };
+
+    // We also implement drop to prevent unsafe drop implementations on this
+    // type and encourage people to use Finalize. This implementation will
+    // call `Finalize::finalize` if it is safe to do so. With
+    // `#[unsafe_no_drop_check]`, the user provides their own `Drop` instead
+    // and promises that it doesn't dereference any `Gc`s.
+    if no_drop_check {
+        return trace_impl;
+    }
+    let drop_impl = s.unbound_impl(
+        quote!(::std::ops::Drop),
+        quote! {
+            fn drop(&mut self) {
+                if ::gc::finalizer_safe() {
+                    ::gc::Finalize::finalize(self);
+                }
+            }
+        },
+    );
+
+    quote! {
+        #trace_impl
+        #drop_impl
+    }
+}
+
+fn derive_trace_impl(s: &Structure<'_>) -> TokenStream {
     let trace_body = s.each(|bi| quote!(mark(#bi)));
+    let visit_body = s.each(|bi| quote!(::gc::Trace::visit_children(#bi, visitor)));
+    let needs_finalize_body = s.each(|bi| {
+        quote! {
+            if ::gc::Trace::needs_finalize(#bi) {
+                return true;
+            }
+        }
+    });
 
-    s.add_bounds(AddBounds::Fields);
-    let trace_impl = s.unsafe_bound_impl(
+    s.unsafe_bound_impl(
         quote!(::gc::Trace),
         quote! {
             #[inline] unsafe fn trace(&self) {
@@ -49,32 +113,139 @@ fn derive_trace(mut s: Structure<'_>) -> proc_macro2::TokenStream {
                 }
                 match *self { #trace_body }
             }
+            #[inline] fn needs_finalize(&self) -> bool {
+                if ::gc::Finalize::has_finalizer(self) {
+                    return true;
+                }
+                match *self { #needs_finalize_body }
+                false
+            }
+            #[inline] unsafe fn visit_children(
+                &self,
+                #[allow(unused_variables)] visitor: &mut dyn ::std::ops::FnMut(::gc::GcPointer),
+            ) {
+                match *self { #visit_body }
+            }
         },
-    );
+    )
+}
 
-    // We also implement drop to prevent unsafe drop implementations on this
-    // type and encourage people to use Finalize. This implementation will
-    // call `Finalize::finalize` if it is safe to do so.
-    let drop_impl = s.unbound_impl(
-        quote!(::std::ops::Drop),
+/// Implements `Trace` for a union, by tracing the single field named in its
+/// `#[unsafe_union_trace(field)]` attribute.
+fn derive_union_trace(input: &DeriveInput, data: &DataUnion) -> syn::Result<TokenStream> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("unsafe_union_trace"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(Trace)] on a union requires #[unsafe_union_trace(field)], \
+                 naming the field which is always initialized",
+            )
+        })?;
+    let field: Ident = attr.parse_args()?;
+    let ty = &data
+        .fields
+        .named
+        .iter()
+        .find(|f| f.ident.as_ref() == Some(&field))
+        .ok_or_else(|| syn::Error::new_spanned(&field, "no such field in this union"))?
+        .ty;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // As for structs and enums, unless the user provides their own `Drop`.
+    let drop_impl = if input
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("unsafe_no_drop_check"))
+    {
+        quote!()
+    } else {
         quote! {
-            fn drop(&mut self) {
-                if ::gc::finalizer_safe() {
-                    ::gc::Finalize::finalize(self);
+            impl #impl_generics ::std::ops::Drop for #name #ty_generics #where_clause {
+                fn drop(&mut self) {
+                    if ::gc::finalizer_safe() {
+                        ::gc::Finalize::finalize(self);
+                    }
                 }
             }
-        },
-    );
+        }
+    };
 
-    quote! {
-        #trace_impl
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    where_clause
+        .predicates
+        .push(syn::parse_quote!(#ty: ::gc::Trace));
+
+    // Reading the field is only sound if the user upholds the promise made
+    // by the attribute.
+    Ok(quote! {
         #drop_impl
-    }
+
+        unsafe impl #impl_generics ::gc::Trace for #name #ty_generics #where_clause {
+            #[inline] unsafe fn trace(&self) {
+                ::gc::Trace::trace(&self.#field);
+            }
+            #[inline] unsafe fn root(&self) {
+                ::gc::Trace::root(&self.#field);
+            }
+            #[inline] unsafe fn unroot(&self) {
+                ::gc::Trace::unroot(&self.#field);
+            }
+            #[inline] fn finalize_glue(&self) {
+                ::gc::Finalize::finalize(self);
+                unsafe { ::gc::Trace::finalize_glue(&self.#field) }
+            }
+            #[inline] fn needs_finalize(&self) -> bool {
+                ::gc::Finalize::has_finalizer(self)
+                    || unsafe { ::gc::Trace::needs_finalize(&self.#field) }
+            }
+            #[inline] unsafe fn visit_children(
+                &self,
+                visitor: &mut dyn ::std::ops::FnMut(::gc::GcPointer),
+            ) {
+                ::gc::Trace::visit_children(&self.#field, visitor);
+            }
+        }
+    })
 }
 
-decl_derive!([Finalize] => derive_finalize);
+#[proc_macro_derive(Finalize)]
+#[allow(non_snake_case)]
+pub fn Finalize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let result = match &input.data {
+        Data::Union(_) => Ok(derive_union_finalize(&input)),
+        _ => Structure::try_new(&input).map(derive_finalize),
+    };
+    result.unwrap_or_else(|e| e.to_compile_error()).into()
+}
 
 #[allow(clippy::needless_pass_by_value)]
-fn derive_finalize(s: Structure<'_>) -> proc_macro2::TokenStream {
-    s.unbound_impl(quote!(::gc::Finalize), quote!())
+fn derive_finalize(s: Structure<'_>) -> TokenStream {
+    s.unbound_impl(
+        quote!(::gc::Finalize),
+        quote! {
+            #[inline] fn has_finalizer(&self) -> bool {
+                false
+            }
+        },
+    )
+}
+
+fn derive_union_finalize(input: &DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::gc::Finalize for #name #ty_generics #where_clause {
+            #[inline] fn has_finalizer(&self) -> bool {
+                false
+            }
+        }
+    }
 }